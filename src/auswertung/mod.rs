@@ -0,0 +1,101 @@
+use serde::Serialize;
+
+use crate::hydraulic::*;
+
+/**
+Größte Wasserspiegelneigung in der Kammer in mm/m.
+Angenähert aus der Durchflusszunahme über die Schwallwelle in der Kammer.
+*/
+pub fn wasserspiegelneigung(schl: &Schleuse, res: &[Simulationsschritt]) -> f64 {
+    let erg = res
+        .iter()
+        .map(|s| {
+            //let wellengeschwindigkeit = (s.kammerwasserspiegel * 9.81).sqrt();
+
+            s.durchflusszunahme / (schl.kammer.breite * 4.0 * 9.81) * 1000.0
+        })
+        .fold(0.0, |max, val: f64| val.max(max));
+    erg
+}
+
+// Abstand zum Oberwasser in m, ab dem die Füllzeit nicht mehr aus den Zeitschritten interpoliert wird
+const RESTABSTAND: f64 = 0.01;
+
+/**
+Füllzeit, zu der die Kammer das Oberwasser erreicht, aus den Zeitschritten extrapoliert.
+Gegen Ende nimmt der Abstand zum Oberwasser etwa quadratisch mit der Restzeit ab, daher wird
+seine Wurzel aus den beiden Zeitschritten um das Unterschreiten von `RESTABSTAND` linear bis auf
+Null verlängert. Direkt am Oberwasser ist die Lösung nicht glatt und die Schrittweitensteuerung
+streut, so hängt die Zeit stetig von den Eingaben ab und eignet sich für Ableitungen.
+*/
+pub fn interpolierte_fuellzeit(schl: &Schleuse, res: &[Simulationsschritt]) -> f64 {
+    let ziel = schl.oberhaupt.oberwasser - schl.unterhaupt.unterwassersohle;
+    // Wurzel des Abstands zum Oberwasser, nach einem Überschießen negativ
+    let wurzel = |s: &Simulationsschritt| {
+        let abstand = ziel - s.kammerwasserspiegel;
+        abstand.signum() * abstand.abs().sqrt()
+    };
+    let nah = res
+        .iter()
+        .position(|s| ziel - s.kammerwasserspiegel <= RESTABSTAND);
+    match nah {
+        Some(i) if i > 0 && wurzel(&res[i - 1]) > wurzel(&res[i]) => {
+            let (vorher, nachher) = (&res[i - 1], &res[i]);
+            let (r0, r1) = (wurzel(vorher), wurzel(nachher));
+            vorher.zeitschritt + (nachher.zeitschritt - vorher.zeitschritt) * r0 / (r0 - r1)
+        }
+        _ => res.last().map_or(0.0, |s| s.zeitschritt),
+    }
+}
+
+// Anzahl der Querschnitte, die während der Simulation vollständig geöffnet wurden
+pub fn fuelloeffnungen(_schl: &Schleuse, res: &[Simulationsschritt]) -> usize {
+    let mut count = 0;
+    res.iter().filter(|&f| !f.events.is_empty()).for_each(|f| {
+        let ev = &f.events;
+        let oeffen = ev
+            .iter()
+            .filter(|eve| eve.status == FuellsystemStatus::VollGeoeffnet)
+            .count();
+        count += oeffen
+    });
+
+    count
+}
+
+/**
+Kennwerte einer Füllung, wie sie die Studien in ihre Ergebnistabellen schreiben.
+Sie genügen auch für alle Nachweise (`Nachweise::pruefe_kennwerte`).
+*/
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct Kennwerte {
+    pub fuellzeit: f64,             // in s
+    pub max_durchfluss: f64,        // in m³/s
+    pub max_durchflusszunahme: f64, // in m³/s²
+    pub wasserspiegelneigung: f64,  // in mm/m
+    pub fuelloeffnungen: usize,
+    pub min_durchfluss: f64,        // in m³/s
+    pub min_durchflusszunahme: f64, // in m³/s²
+    pub fuellquerschnitte: usize,
+}
+
+impl Kennwerte {
+    pub fn berechne(schl: &Schleuse, res: &[Simulationsschritt]) -> Self {
+        Kennwerte {
+            fuellzeit: res.last().map_or(0.0, |s| s.zeitschritt),
+            max_durchfluss: res.iter().map(|s| s.durchfluss).fold(0.0, f64::max),
+            max_durchflusszunahme: res.iter().map(|s| s.durchflusszunahme).fold(0.0, f64::max),
+            wasserspiegelneigung: wasserspiegelneigung(schl, res),
+            fuelloeffnungen: fuelloeffnungen(schl, res),
+            min_durchfluss: res
+                .iter()
+                .map(|s| s.durchfluss)
+                .fold(f64::INFINITY, f64::min),
+            min_durchflusszunahme: res
+                .iter()
+                .map(|s| s.durchflusszunahme)
+                .fold(f64::INFINITY, f64::min),
+            fuellquerschnitte: schl.fuellsystem.anzahl_fuellsysteme(),
+        }
+    }
+}
@@ -1,93 +1,662 @@
+use serde::{Deserialize, Serialize};
+
 use crate::hydraulic::*;
 
 pub struct FuellRechteck {
     pub oeffnungsgeschwindigkeit: f64, // in m/s
     pub breite: f64,
     pub hoehe: f64,
+    pub verluste: Verlustbeiwerte,
+}
+
+/**
+Verlustbeiwerte und Abflusskurven eines Füllquerschnitts.
+Die Standardwerte entsprechen der Vorlage `standard`.
+*/
+#[derive(Clone, Debug, PartialEq)]
+pub struct Verlustbeiwerte {
+    pub einlauf: f64,        // Verlustbeiwert des Einlaufs
+    pub aufweitung: f64,     // Faktor auf den Borda-Carnot-Verlust der Aufweitung im Querschnitt
+    pub austritt: f64,       // Faktor auf den Borda-Carnot-Verlust beim Austritt in die Kammer
+    pub ueberfall: Vec<f64>, // Abflussbeiwert des Ausflusses aus einem Rechteck als Polynom, aufsteigende Potenzen
+    pub kontraktion: f64,    // Einschnürungszahl an der scharfkantigen Unterkante eines Schützes
+    pub umlenkung: f64,      // Verlustbeiwert der Umlenkung im Schacht eines Zylinderschützes
+}
+
+/**
+Benannte Sätze von Verlustbeiwerten aus der Literatur.
+Alle Vorlagen gehen von der Vorlage `standard` aus und ändern nur die genannten Beiwerte.
+*/
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum Vorlage {
+    // Scharfkantiger Einlauf (ζ = 0,5) und um 20 % erhöhte Stoßverluste, bisherige Werte des Programms
+    Standard,
+    // Gebrochene bzw. angefaste Einlaufkante (ζ = 0,25)
+    Gebrochen,
+    // Gut abgerundeter Einlauf (ζ = 0,1), etwa nach Idelchik für r/d ≥ 0,1
+    Abgerundet,
+    // Stoßverluste ohne Zuschlag nach Borda-Carnot
+    Bordacarnot,
+}
+
+impl Vorlage {
+    pub fn beiwerte(&self) -> Verlustbeiwerte {
+        let standard = Verlustbeiwerte::default();
+        match self {
+            Vorlage::Standard => standard,
+            Vorlage::Gebrochen => Verlustbeiwerte {
+                einlauf: 0.25,
+                ..standard
+            },
+            Vorlage::Abgerundet => Verlustbeiwerte {
+                einlauf: 0.1,
+                ..standard
+            },
+            Vorlage::Bordacarnot => Verlustbeiwerte {
+                aufweitung: 1.0,
+                austritt: 1.0,
+                ..standard
+            },
+        }
+    }
+}
+
+impl Default for Verlustbeiwerte {
+    fn default() -> Self {
+        Verlustbeiwerte {
+            einlauf: 0.5,
+            aufweitung: 1.2,
+            austritt: 1.2,
+            // I = 0.018 x^4 + -0.047333 x^3 - 0.0105 x^2 - 0.0511667 x^1 + 0.673
+            ueberfall: vec![0.673, -0.0511667, -0.0105, -0.047333, 0.018],
+            kontraktion: 0.61,
+            umlenkung: 0.3,
+        }
+    }
 }
 
 fn dhyd(area: f64, umfang: f64) -> f64 {
-    return 4.0 * area / umfang;
+    4.0 * area / umfang
 }
 
 impl Fuellquerschnitt for FuellRechteck {
     fn querschnitt(&self, zeit: f64) -> f64 {
         let temp = zeit * self.oeffnungsgeschwindigkeit;
-        return if temp > self.hoehe {
+        if temp > self.hoehe {
             self.breite * self.hoehe
         } else {
             self.breite * temp
-        };
+        }
     }
 
     fn querschnitt_prozent_zeit(&self, zeit: f64) -> f64 {
-        return self.freigegebene_hoehe(zeit) / self.hoehe;
+        self.freigegebene_hoehe(zeit) / self.hoehe
     }
 
     fn querschnitt_prozent_hoehe(&self, hoehe: f64) -> f64 {
-        return (hoehe).min(self.hoehe) / self.hoehe;
+        (hoehe).min(self.hoehe) / self.hoehe
     }
 
     fn freigegebene_hoehe(&self, zeit: f64) -> f64 {
-        return (zeit * self.oeffnungsgeschwindigkeit).min(self.hoehe);
+        (zeit * self.oeffnungsgeschwindigkeit).min(self.hoehe)
     }
 
     fn freigegebene_breite(&self, _hoehe: f64) -> f64 {
-        return self.breite;
+        self.breite
     }
 
     fn is_fully_opened(&self, zeit: f64) -> bool {
-        return zeit * self.oeffnungsgeschwindigkeit > self.hoehe;
+        zeit * self.oeffnungsgeschwindigkeit > self.hoehe
     }
 
     fn durchflussverslust_ueberfall(
         &self,
-        _schleuse: &Schleuse,
+        _abstrom: &Abstrom,
         _pot_hoehe: f64,
         unterehoehe: f64,
         zeit: f64,
     ) -> f64 {
-        //Äquivalente QS Breite
-        let b = self.querschnitt(zeit) / (self.freigegebene_hoehe(zeit) - unterehoehe);
-        let x = (self.freigegebene_hoehe(zeit) - unterehoehe) / b;
-        return 0.673 + x * (-0.0511667 + x * (-0.0105 + x * (-0.047333 + x * (0.018))));
+        verlust_ueberfall_rechteck(
+            &self.verluste.ueberfall,
+            self.breite,
+            self.freigegebene_hoehe(zeit),
+            unterehoehe,
+        )
     }
 
     fn durchflussverslust_schuetz(&self, pot_hoehe: f64, unterehoehe: f64, zeit: f64) -> f64 {
-        return 0.0;
+        schuetz_abflussbeiwert(
+            self.freigegebene_hoehe(zeit),
+            pot_hoehe,
+            unterehoehe,
+            einschnuerungszahl(
+                self.verluste.kontraktion,
+                self.querschnitt_prozent_zeit(zeit),
+            ),
+        )
     }
 
     fn durchflussverslust_unterstroemung(
         &self,
-        schleuse: &Schleuse,
+        abstrom: &Abstrom,
+        _pot_hoehe: f64,
+        _unterehoehe: f64,
+        zeit: f64,
+    ) -> f64 {
+        verlust_unterstroemung_rechteck(
+            abstrom,
+            &self.verluste,
+            self.breite,
+            self.hoehe,
+            self.freigegebene_hoehe(zeit),
+        )
+    }
+}
+
+/**
+Rechteckiger Füllquerschnitt, dessen freigegebene Höhe einem stückweise linearen Hubgesetz folgt.
+Das Gesetz ist als Stützstellen (Zeit ab Öffnungsbeginn in s, Höhe in m) gegeben,
+etwa langsamer Anlauf, Halten und schnelles Öffnen, oder als aufgezeichneter Schützweg.
+Vor der ersten und nach der letzten Stützstelle bleibt die Höhe konstant.
+*/
+pub struct FuellRechteckGesetz {
+    pub breite: f64,
+    pub hoehe: f64,
+    pub stuetzstellen: Vec<(f64, f64)>, // Nach der Zeit sortiert
+    pub verluste: Verlustbeiwerte,
+}
+
+impl FuellRechteckGesetz {
+    fn hubhoehe(&self, zeit: f64) -> f64 {
+        let erste = match self.stuetzstellen.first() {
+            Some(s) => s,
+            None => return 0.0,
+        };
+        if zeit <= erste.0 {
+            return erste.1;
+        }
+        for paar in self.stuetzstellen.windows(2) {
+            let (t0, h0) = paar[0];
+            let (t1, h1) = paar[1];
+            if zeit <= t1 {
+                if t1 <= t0 {
+                    return h1;
+                }
+                return h0 + (h1 - h0) * (zeit - t0) / (t1 - t0);
+            }
+        }
+        self.stuetzstellen.last().unwrap().1
+    }
+}
+
+impl Fuellquerschnitt for FuellRechteckGesetz {
+    fn querschnitt(&self, zeit: f64) -> f64 {
+        self.breite * self.freigegebene_hoehe(zeit)
+    }
+
+    fn querschnitt_prozent_zeit(&self, zeit: f64) -> f64 {
+        self.freigegebene_hoehe(zeit) / self.hoehe
+    }
+
+    fn querschnitt_prozent_hoehe(&self, hoehe: f64) -> f64 {
+        (hoehe).min(self.hoehe) / self.hoehe
+    }
+
+    fn freigegebene_hoehe(&self, zeit: f64) -> f64 {
+        self.hubhoehe(zeit).clamp(0.0, self.hoehe)
+    }
+
+    fn freigegebene_breite(&self, _hoehe: f64) -> f64 {
+        self.breite
+    }
+
+    fn is_fully_opened(&self, zeit: f64) -> bool {
+        self.hubhoehe(zeit) >= self.hoehe
+    }
+
+    fn durchflussverslust_ueberfall(
+        &self,
+        _abstrom: &Abstrom,
         _pot_hoehe: f64,
         unterehoehe: f64,
         zeit: f64,
     ) -> f64 {
-        //Einlauf
-        let areafull = self.breite * self.hoehe;
-        let areafree = self.breite * self.freigegebene_hoehe(zeit);
-
-        let z1: f64 = 0.5;
-        // Ausweitung
-        let z2: f64 = 1.2
-            * (1.0
-                - dhyd(
-                    areafree,
-                    2.0 * (self.breite + self.freigegebene_hoehe(zeit)),
-                ) / dhyd(areafull, 2.0 * (self.breite + self.hoehe)))
-            .powi(2);
-        // Ausweitung
-        let kammerwasserspiegel =
-            unterehoehe + schleuse.oberhaupt.oberwassersohle - schleuse.unterhaupt.unterwassersohle;
-        let z3 = 1.2
-            * (1.0
-                - dhyd(areafree, 2.0 * (self.breite * self.hoehe))
-                    / dhyd(
-                        schleuse.kammer.breite * kammerwasserspiegel,
-                        (schleuse.kammer.breite) + 2.0 * kammerwasserspiegel,
-                    ))
+        verlust_ueberfall_rechteck(
+            &self.verluste.ueberfall,
+            self.breite,
+            self.freigegebene_hoehe(zeit),
+            unterehoehe,
+        )
+    }
+
+    fn durchflussverslust_schuetz(&self, pot_hoehe: f64, unterehoehe: f64, zeit: f64) -> f64 {
+        schuetz_abflussbeiwert(
+            self.freigegebene_hoehe(zeit),
+            pot_hoehe,
+            unterehoehe,
+            einschnuerungszahl(
+                self.verluste.kontraktion,
+                self.querschnitt_prozent_zeit(zeit),
+            ),
+        )
+    }
+
+    fn durchflussverslust_unterstroemung(
+        &self,
+        abstrom: &Abstrom,
+        _pot_hoehe: f64,
+        _unterehoehe: f64,
+        zeit: f64,
+    ) -> f64 {
+        verlust_unterstroemung_rechteck(
+            abstrom,
+            &self.verluste,
+            self.breite,
+            self.hoehe,
+            self.freigegebene_hoehe(zeit),
+        )
+    }
+}
+
+/**
+Kreisrunder Rohrquerschnitt, der von einem senkrecht gehobenen Schieber freigegeben wird.
+Die freigegebene Fläche ist das Kreissegment unterhalb der Schieberunterkante.
+*/
+pub struct FuellKreis {
+    pub oeffnungsgeschwindigkeit: f64, // in m/s
+    pub durchmesser: f64,
+    pub verluste: Verlustbeiwerte,
+}
+
+impl FuellKreis {
+    fn vollflaeche(&self) -> f64 {
+        std::f64::consts::PI * self.durchmesser.powi(2) / 4.0
+    }
+
+    // Fläche des Kreissegments bis zur Höhe `hoehe` über der Rohrsohle
+    fn segmentflaeche(&self, hoehe: f64) -> f64 {
+        let r = self.durchmesser / 2.0;
+        let h = hoehe.clamp(0.0, self.durchmesser);
+        r.powi(2) * ((r - h) / r).acos() - (r - h) * (2.0 * r * h - h.powi(2)).sqrt()
+    }
+}
+
+impl Fuellquerschnitt for FuellKreis {
+    fn querschnitt(&self, zeit: f64) -> f64 {
+        self.segmentflaeche(self.freigegebene_hoehe(zeit))
+    }
+
+    fn querschnitt_prozent_zeit(&self, zeit: f64) -> f64 {
+        self.querschnitt(zeit) / self.vollflaeche()
+    }
+
+    fn querschnitt_prozent_hoehe(&self, hoehe: f64) -> f64 {
+        self.segmentflaeche(hoehe) / self.vollflaeche()
+    }
+
+    fn freigegebene_hoehe(&self, zeit: f64) -> f64 {
+        (zeit * self.oeffnungsgeschwindigkeit).min(self.durchmesser)
+    }
+
+    fn freigegebene_breite(&self, hoehe: f64) -> f64 {
+        // Sehnenlänge des Kreises
+        2.0 * (hoehe * (self.durchmesser - hoehe)).max(0.0).sqrt()
+    }
+
+    fn is_fully_opened(&self, zeit: f64) -> bool {
+        zeit * self.oeffnungsgeschwindigkeit > self.durchmesser
+    }
+
+    fn durchflussverslust_ueberfall(
+        &self,
+        _abstrom: &Abstrom,
+        _pot_hoehe: f64,
+        _unterehoehe: f64,
+        zeit: f64,
+    ) -> f64 {
+        verlust_einschnuerung(
+            self.querschnitt_prozent_zeit(zeit),
+            self.verluste.kontraktion,
+            self.verluste.einlauf + 1.0,
+        )
+    }
+
+    fn durchflussverslust_schuetz(&self, pot_hoehe: f64, unterehoehe: f64, zeit: f64) -> f64 {
+        schuetz_abflussbeiwert(
+            self.freigegebene_hoehe(zeit),
+            pot_hoehe,
+            unterehoehe,
+            einschnuerungszahl(
+                self.verluste.kontraktion,
+                self.querschnitt_prozent_zeit(zeit),
+            ),
+        )
+    }
+
+    fn durchflussverslust_unterstroemung(
+        &self,
+        abstrom: &Abstrom,
+        _pot_hoehe: f64,
+        _unterehoehe: f64,
+        zeit: f64,
+    ) -> f64 {
+        verlust_einschnuerung(
+            self.querschnitt_prozent_zeit(zeit),
+            self.verluste.kontraktion,
+            self.verluste.einlauf
+                + self.verluste.austritt * aufweitung_abstrom(abstrom, self.vollflaeche()),
+        )
+    }
+}
+
+/**
+Zylinderschütz: ein senkrechter Zylinder wird um den Hub angehoben und gibt ein Fenster
+über den ganzen Umfang frei. Das Wasser strömt radial zu, wird umgelenkt und fließt durch den Schacht ab.
+*/
+pub struct FuellZylinder {
+    pub oeffnungsgeschwindigkeit: f64, // in m/s
+    pub durchmesser: f64,
+    pub hub: f64, // Größter Hub in m
+    pub verluste: Verlustbeiwerte,
+}
+
+impl FuellZylinder {
+    fn schachtflaeche(&self) -> f64 {
+        std::f64::consts::PI * self.durchmesser.powi(2) / 4.0
+    }
+}
+
+impl Fuellquerschnitt for FuellZylinder {
+    fn querschnitt(&self, zeit: f64) -> f64 {
+        std::f64::consts::PI * self.durchmesser * self.freigegebene_hoehe(zeit)
+    }
+
+    fn querschnitt_prozent_zeit(&self, zeit: f64) -> f64 {
+        self.freigegebene_hoehe(zeit) / self.hub
+    }
+
+    fn querschnitt_prozent_hoehe(&self, hoehe: f64) -> f64 {
+        (hoehe).min(self.hub) / self.hub
+    }
+
+    fn freigegebene_hoehe(&self, zeit: f64) -> f64 {
+        (zeit * self.oeffnungsgeschwindigkeit).min(self.hub)
+    }
+
+    fn freigegebene_breite(&self, _hoehe: f64) -> f64 {
+        std::f64::consts::PI * self.durchmesser
+    }
+
+    fn is_fully_opened(&self, zeit: f64) -> bool {
+        zeit * self.oeffnungsgeschwindigkeit > self.hub
+    }
+
+    fn durchflussverslust_ueberfall(
+        &self,
+        _abstrom: &Abstrom,
+        _pot_hoehe: f64,
+        _unterehoehe: f64,
+        zeit: f64,
+    ) -> f64 {
+        verlust_einschnuerung(
+            self.querschnitt(zeit) / self.schachtflaeche(),
+            self.verluste.kontraktion,
+            self.verluste.umlenkung + 1.0,
+        )
+    }
+
+    fn durchflussverslust_schuetz(&self, pot_hoehe: f64, unterehoehe: f64, zeit: f64) -> f64 {
+        schuetz_abflussbeiwert(
+            self.freigegebene_hoehe(zeit),
+            pot_hoehe,
+            unterehoehe,
+            einschnuerungszahl(
+                self.verluste.kontraktion,
+                self.querschnitt(zeit) / self.schachtflaeche(),
+            ),
+        )
+    }
+
+    fn durchflussverslust_unterstroemung(
+        &self,
+        abstrom: &Abstrom,
+        _pot_hoehe: f64,
+        _unterehoehe: f64,
+        zeit: f64,
+    ) -> f64 {
+        verlust_einschnuerung(
+            self.querschnitt(zeit) / self.schachtflaeche(),
+            self.verluste.kontraktion,
+            self.verluste.umlenkung
+                + self.verluste.austritt * aufweitung_abstrom(abstrom, self.schachtflaeche()),
+        )
+    }
+}
+
+/**
+Segmentschütz vor einem rechteckigen Querschnitt. Die Unterkante hebt sich mit der
+Öffnungsgeschwindigkeit, die Einschnürung hängt vom Winkel der Schützhaut an der Unterkante ab.
+Der Drehpunkt liegt `drehpunkthoehe` über der Schwelle.
+*/
+pub struct FuellSegment {
+    pub oeffnungsgeschwindigkeit: f64, // in m/s
+    pub breite: f64,
+    pub hoehe: f64,
+    pub radius: f64,
+    pub drehpunkthoehe: f64,
+    pub verluste: Verlustbeiwerte,
+}
+
+impl FuellSegment {
+    /**
+    Einschnürungszahl abhängig vom Winkel zwischen Schützhaut und Sohle an der Unterkante,
+    angenähert nach Toch bzw. Henderson. Bei 90° entspricht sie dem ebenen Schütz.
+    */
+    fn kontraktion(&self, zeit: f64) -> f64 {
+        let cos =
+            ((self.drehpunkthoehe - self.freigegebene_hoehe(zeit)) / self.radius).clamp(-1.0, 1.0);
+        let winkel = cos.acos().to_degrees() / 90.0;
+        1.0 - 0.75 * winkel + 0.36 * winkel.powi(2)
+    }
+}
+
+impl Fuellquerschnitt for FuellSegment {
+    fn querschnitt(&self, zeit: f64) -> f64 {
+        self.breite * self.freigegebene_hoehe(zeit)
+    }
+
+    fn querschnitt_prozent_zeit(&self, zeit: f64) -> f64 {
+        self.freigegebene_hoehe(zeit) / self.hoehe
+    }
+
+    fn querschnitt_prozent_hoehe(&self, hoehe: f64) -> f64 {
+        (hoehe).min(self.hoehe) / self.hoehe
+    }
+
+    fn freigegebene_hoehe(&self, zeit: f64) -> f64 {
+        (zeit * self.oeffnungsgeschwindigkeit).min(self.hoehe)
+    }
+
+    fn freigegebene_breite(&self, _hoehe: f64) -> f64 {
+        self.breite
+    }
+
+    fn is_fully_opened(&self, zeit: f64) -> bool {
+        zeit * self.oeffnungsgeschwindigkeit > self.hoehe
+    }
+
+    fn durchflussverslust_ueberfall(
+        &self,
+        _abstrom: &Abstrom,
+        _pot_hoehe: f64,
+        _unterehoehe: f64,
+        zeit: f64,
+    ) -> f64 {
+        verlust_einschnuerung(
+            self.querschnitt_prozent_zeit(zeit),
+            self.kontraktion(zeit),
+            self.verluste.einlauf + 1.0,
+        )
+    }
+
+    fn durchflussverslust_schuetz(&self, pot_hoehe: f64, unterehoehe: f64, zeit: f64) -> f64 {
+        schuetz_abflussbeiwert(
+            self.freigegebene_hoehe(zeit),
+            pot_hoehe,
+            unterehoehe,
+            einschnuerungszahl(self.kontraktion(zeit), self.querschnitt_prozent_zeit(zeit)),
+        )
+    }
+
+    fn durchflussverslust_unterstroemung(
+        &self,
+        abstrom: &Abstrom,
+        _pot_hoehe: f64,
+        _unterehoehe: f64,
+        zeit: f64,
+    ) -> f64 {
+        verlust_einschnuerung(
+            self.querschnitt_prozent_zeit(zeit),
+            self.kontraktion(zeit),
+            self.verluste.einlauf
+                + self.verluste.austritt * aufweitung_abstrom(abstrom, self.breite * self.hoehe),
+        )
+    }
+}
+
+/**
+Abflussbeiwert bezogen auf die freigegebene Fläche für einen teilweise geöffneten Verschluss.
+`verhaeltnis` ist freigegebene zu voller Fläche, `kontraktion` die Einschnürungszahl bei kleiner Öffnung
+und `zeta` die Summe der übrigen Verlustbeiwerte bezogen auf die Geschwindigkeit im vollen Querschnitt.
+Die Einschnürung geht nach Weisbach mit zunehmender Öffnung gegen 1,
+die Aufweitung hinter dem eingeschnürten Strahl folgt Borda-Carnot.
+*/
+fn verlust_einschnuerung(verhaeltnis: f64, kontraktion: f64, zeta: f64) -> f64 {
+    let n = verhaeltnis.clamp(0.0, 1.0);
+    let psi = einschnuerungszahl(kontraktion, n);
+    1.0 / (n.powi(2) * zeta + (1.0 / psi - n).powi(2)).sqrt()
+}
+
+// Einschnürungszahl nach Weisbach, von `kontraktion` bei kleiner Öffnung bis 1 bei voller Öffnung
+fn einschnuerungszahl(kontraktion: f64, verhaeltnis: f64) -> f64 {
+    let n = verhaeltnis.clamp(0.0, 1.0);
+    kontraktion + (1.0 - kontraktion) * n.powi(3)
+}
+
+// Austrittsverlust beim Übergang aus dem Querschnitt mit der Fläche `flaeche` in Kammer bzw. Unterwasser
+fn aufweitung_abstrom(abstrom: &Abstrom, flaeche: f64) -> f64 {
+    (1.0 - flaeche / (abstrom.breite * abstrom.wassertiefe))
+        .max(0.0)
+        .powi(2)
+}
+
+// Abflussbeiwert des Ausflusses aus einem rechteckigen Querschnitt mit der freigegebenen Höhe `frei`
+fn verlust_ueberfall_rechteck(polynom: &[f64], breite: f64, frei: f64, unterehoehe: f64) -> f64 {
+    //Äquivalente QS Breite
+    let b = breite * frei / (frei - unterehoehe);
+    let x = (frei - unterehoehe) / b;
+    polynom.iter().rev().fold(0.0, |summe, a| summe * x + a)
+}
+
+// Abflussbeiwert der Unterströmung aus Einlauf- und Ausweitungsverlusten eines rechteckigen Querschnitts
+fn verlust_unterstroemung_rechteck(
+    abstrom: &Abstrom,
+    verluste: &Verlustbeiwerte,
+    breite: f64,
+    hoehe: f64,
+    frei: f64,
+) -> f64 {
+    //Einlauf
+    let areafull = breite * hoehe;
+    let areafree = breite * frei;
+
+    let z1: f64 = verluste.einlauf;
+    // Ausweitung
+    let z2: f64 = verluste.aufweitung
+        * (1.0 - dhyd(areafree, 2.0 * (breite + frei)) / dhyd(areafull, 2.0 * (breite + hoehe)))
             .powi(2);
-        return (1.0) / ((1.0 + z1.max(0.0) + z2.max(0.0) + z3.max(0.0)).sqrt());
+    // Austritt in Kammer bzw. Unterwasser
+    let z3 = verluste.austritt
+        * (1.0
+            - dhyd(areafree, 2.0 * (breite * hoehe))
+                / dhyd(
+                    abstrom.breite * abstrom.wassertiefe,
+                    abstrom.breite + 2.0 * abstrom.wassertiefe,
+                ))
+        .powi(2);
+    (1.0) / ((1.0 + z1.max(0.0) + z2.max(0.0) + z3.max(0.0)).sqrt())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+
+    fn gleich(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-12
     }
-} // I = 0.018 x^4 + -0.047333 x^3 - 0.0105 x^2 - 0.0511667 x^1 + 0.673
+
+    #[test]
+    fn kreis_segmentflaeche_und_sehne() {
+        // Durchmesser 2 m, nach 100 s halb und nach 200 s ganz geöffnet
+        let kreis = FuellKreis {
+            oeffnungsgeschwindigkeit: 0.01,
+            durchmesser: 2.0,
+            verluste: Verlustbeiwerte::default(),
+        };
+        assert!(gleich(kreis.querschnitt(0.0), 0.0));
+        assert!(gleich(kreis.freigegebene_breite(0.0), 0.0));
+        assert!(gleich(kreis.querschnitt(100.0), PI / 2.0));
+        assert!(gleich(kreis.querschnitt_prozent_zeit(100.0), 0.5));
+        assert!(gleich(kreis.freigegebene_breite(1.0), 2.0));
+        assert!(gleich(kreis.querschnitt(200.0), PI));
+        assert!(gleich(kreis.querschnitt_prozent_hoehe(2.0), 1.0));
+        assert!(gleich(kreis.freigegebene_breite(2.0), 0.0));
+        // Bei einem Viertel des Durchmessers: r² (π/3 - √3/4) und Sehne √3
+        assert!(gleich(
+            kreis.querschnitt(50.0),
+            PI / 3.0 - 3.0_f64.sqrt() / 4.0
+        ));
+        assert!(gleich(kreis.freigegebene_breite(0.5), 3.0_f64.sqrt()));
+        assert!(!kreis.is_fully_opened(200.0) && kreis.is_fully_opened(201.0));
+        assert!(gleich(kreis.querschnitt(300.0), PI));
+    }
+
+    #[test]
+    fn zylinder_fenster_ueber_den_umfang() {
+        let zylinder = FuellZylinder {
+            oeffnungsgeschwindigkeit: 0.01,
+            durchmesser: 1.0,
+            hub: 0.6,
+            verluste: Verlustbeiwerte::default(),
+        };
+        assert!(gleich(zylinder.querschnitt(0.0), 0.0));
+        assert!(gleich(zylinder.querschnitt(30.0), PI * 0.3));
+        assert!(gleich(zylinder.querschnitt_prozent_zeit(30.0), 0.5));
+        assert!(gleich(zylinder.freigegebene_breite(0.3), PI));
+        assert!(gleich(zylinder.querschnitt(60.0), PI * 0.6));
+        assert!(gleich(zylinder.querschnitt_prozent_zeit(60.0), 1.0));
+        assert!(gleich(zylinder.querschnitt(90.0), PI * 0.6));
+    }
+
+    #[test]
+    fn segment_flaeche_und_einschnuerung() {
+        let segment = FuellSegment {
+            oeffnungsgeschwindigkeit: 0.01,
+            breite: 3.0,
+            hoehe: 2.0,
+            radius: 4.0,
+            drehpunkthoehe: 2.0,
+            verluste: Verlustbeiwerte::default(),
+        };
+        assert!(gleich(segment.querschnitt(0.0), 0.0));
+        assert!(gleich(segment.querschnitt(100.0), 3.0));
+        assert!(gleich(segment.querschnitt_prozent_zeit(100.0), 0.5));
+        assert!(gleich(segment.querschnitt(200.0), 6.0));
+        assert!(gleich(segment.querschnitt_prozent_zeit(200.0), 1.0));
+        // Geschlossen steht die Schützhaut unter 60° zur Sohle, auf Drehpunkthöhe senkrecht wie ein ebenes Schütz
+        assert!(gleich(segment.kontraktion(0.0), 1.0 - 0.5 + 0.16));
+        assert!(gleich(segment.kontraktion(200.0), 0.61));
+    }
+}
@@ -0,0 +1,381 @@
+use clap::error::ErrorKind;
+use clap::{Args, CommandFactory, Parser, Subcommand, ValueEnum};
+
+use kammerwasserspiegel::ersatzmodell::Modellart;
+use kammerwasserspiegel::fileio::Format;
+use kammerwasserspiegel::nachweis::Nachweise;
+use kammerwasserspiegel::optimierung::NelderMead;
+use kammerwasserspiegel::studien::Zielgroesse;
+use kammerwasserspiegel::suche::Suche;
+
+/// Bemessung des Füll- und Entleerungssystems einer Schleusenkammer
+#[derive(Parser)]
+#[command(version, about)]
+pub struct Cli {
+    /// Eingabedatei mit der Beschreibung der Schleuse
+    #[arg(short, long, global = true, default_value = "test.toml")]
+    pub input: String,
+
+    /// Verzeichnis, in das die Ergebnisse geschrieben werden
+    #[arg(short, long, global = true, default_value = ".")]
+    pub output_dir: String,
+
+    /// Anzahl der Threads für Studien, 0 verwendet alle Kerne
+    #[arg(short, long, global = true, default_value_t = 0)]
+    pub threads: usize,
+
+    /// Keine Fortschrittsanzeige, etwa für Skripte
+    #[arg(short, long, global = true)]
+    pub quiet: bool,
+
+    /// Datei, in der die Kennwerte simulierter Varianten für spätere Läufe aufbewahrt werden.
+    /// Sie gilt für alle Builds derselben Programmversion, nach Änderungen am Rechenmodell die Datei löschen
+    #[arg(long, global = true)]
+    pub cache: Option<String>,
+
+    /// Trennzeichen der Ergebnistabellen, mit Dezimalkomma ist Semikolon voreingestellt und Komma nicht erlaubt
+    #[arg(long, global = true, value_enum)]
+    pub separator: Option<Trennzeichen>,
+
+    /// Zahlen mit Dezimalkomma schreiben, etwa für eine deutsche Tabellenkalkulation
+    #[arg(long, global = true)]
+    pub decimal_comma: bool,
+
+    /// Ergebnistabellen ohne Kopfzeile mit Spaltennamen und Einheiten schreiben
+    #[arg(long, global = true)]
+    pub no_header: bool,
+
+    /// Simulationen zusätzlich mit Eingabe, Zeitreihen, Ereignissen und Kennwerten nach result.json schreiben
+    #[arg(long, global = true)]
+    pub json: bool,
+
+    /// Simulationen während der Rechnung zeilenweise nach result.jsonl schreiben
+    #[arg(long, global = true)]
+    pub json_lines: bool,
+
+    #[command(subcommand)]
+    pub befehl: Befehl,
+}
+
+impl Cli {
+    // Wie `Cli::parse`, lehnt aber Dezimalkomma mit Komma als Trennzeichen ab
+    pub fn lies() -> Self {
+        let cli = Cli::parse();
+        if cli.decimal_comma && matches!(cli.separator, Some(Trennzeichen::Comma)) {
+            Cli::command()
+                .error(
+                    ErrorKind::ArgumentConflict,
+                    "--decimal-comma needs a separator other than comma",
+                )
+                .exit()
+        }
+        cli
+    }
+
+    pub fn format(&self) -> Format {
+        let trennzeichen = match (self.separator, self.decimal_comma) {
+            (Some(t), _) => t,
+            (None, true) => Trennzeichen::Semicolon,
+            (None, false) => Trennzeichen::Comma,
+        };
+        Format {
+            trennzeichen: match trennzeichen {
+                Trennzeichen::Comma => ',',
+                Trennzeichen::Semicolon => ';',
+                Trennzeichen::Tab => '\t',
+            },
+            dezimalkomma: self.decimal_comma,
+            kopfzeile: !self.no_header,
+        }
+    }
+}
+
+#[derive(Subcommand)]
+pub enum Befehl {
+    /// Füllen und Entleeren der Schleuse aus der Eingabedatei simulieren
+    Simulate {
+        /// Höhe aller rechteckigen Füllquerschnitte in m
+        #[arg(long)]
+        height: Option<f64>,
+        /// Breite aller rechteckigen Füllquerschnitte in m
+        #[arg(long)]
+        width: Option<f64>,
+        /// Öffnungsgeschwindigkeit aller rechteckigen Füllquerschnitte in m/s
+        #[arg(long)]
+        speed: Option<f64>,
+    },
+    /// Raster über Höhe und Breite für mehrere Öffnungsgeschwindigkeiten (dimenXXX.csv)
+    Sweep {
+        #[command(flatten)]
+        bereiche: Bereiche,
+        #[command(flatten)]
+        grenzen: Grenzen,
+    },
+    /// Kleinste zulässige Höhe je Breite und Öffnungsgeschwindigkeit (min.csv)
+    Minimize {
+        #[command(flatten)]
+        bereiche: Bereiche,
+        #[command(flatten)]
+        grenzen: Grenzen,
+        #[command(flatten)]
+        suche: Suchwerte,
+    },
+    /// Kleinste und größte zulässige Öffnungsgeschwindigkeit je Höhe und Breite (inter_min.csv, inter_max.csv)
+    Interaction {
+        #[command(flatten)]
+        bereiche: Bereiche,
+        #[command(flatten)]
+        grenzen: Grenzen,
+        #[command(flatten)]
+        suche: Suchwerte,
+    },
+    /// Nicht dominierte Varianten aus Füllzeit, Wasserspiegelneigung und Querschnittsfläche (pareto.csv)
+    Pareto {
+        #[command(flatten)]
+        bereiche: Bereiche,
+        #[command(flatten)]
+        grenzen: Grenzen,
+        /// Anzahl der Schritte je Bereich
+        #[arg(long, default_value_t = 10)]
+        steps: u32,
+    },
+    /// Einfluss aller Eingaben auf Füllzeit und Wasserspiegelneigung (sensitivitaet.csv)
+    Sensitivity {
+        /// Relative Störung der Eingaben
+        #[arg(long, default_value_t = 0.01)]
+        step: f64,
+    },
+    /// Monte-Carlo-Simulation mit den Unsicherheiten der Eingabedatei (montecarlo.csv)
+    MonteCarlo {
+        /// Anzahl der Stichproben
+        #[arg(long, default_value_t = 1000)]
+        samples: u32,
+        /// Startwert des Zufallszahlengenerators
+        #[arg(long, default_value_t = 1)]
+        seed: u64,
+        /// Auszugebende Perzentile in %
+        #[arg(long, num_args = 1.., default_values_t = [5.0, 50.0, 95.0])]
+        percentiles: Vec<f64>,
+    },
+    /// Versuchsplan der Eingabedatei auswerten (versuchsplan.csv)
+    Doe,
+    /// Alle Kombinationen der Variationen der Eingabedatei durchrechnen (variation.csv)
+    Vary,
+    /// Füllquerschnitt mit dem Verfahren nach Nelder und Mead optimieren und simulieren
+    Optimize {
+        #[command(flatten)]
+        bereiche: Bereiche,
+        #[command(flatten)]
+        grenzen: Grenzen,
+        /// Zu minimierende Größe
+        #[arg(long, value_enum, default_value_t = Ziel::Area)]
+        objective: Ziel,
+        /// Gewicht der bezogenen Verletzungen der Nachweise
+        #[arg(long, default_value_t = 100.0)]
+        penalty: f64,
+        #[command(flatten)]
+        optimierer: Optimierer,
+    },
+    /// Ersatzmodelle an Ergebnistabellen anpassen und ihre Güte ausgeben (ersatzmodell.toml)
+    Surrogate {
+        /// Ergebnistabellen, etwa versuchsplan.csv
+        #[arg(long, num_args = 1.., required = true)]
+        table: Vec<String>,
+        /// Spalten der Eingänge, ab 0 gezählt
+        #[arg(long, num_args = 1.., required = true)]
+        inputs: Vec<usize>,
+        /// Spalten der Ergebnisgrößen, etwa Füllzeit und Wasserspiegelneigung
+        #[arg(long, num_args = 1.., required = true)]
+        outputs: Vec<usize>,
+        /// Art des Ersatzmodells
+        #[arg(long, value_enum, default_value_t = Art::Poly)]
+        kind: Art,
+        /// Grad des Polynoms
+        #[arg(long, default_value_t = 2)]
+        degree: u32,
+    },
+    /// Gespeicherte Ersatzmodelle an einem Punkt auswerten
+    Query {
+        /// Datei mit den Ersatzmodellen [Standard: ersatzmodell.toml im Ausgabeverzeichnis]
+        #[arg(long)]
+        model: Option<String>,
+        /// Werte der Eingänge in der Reihenfolge der Anpassung
+        #[arg(long, num_args = 1.., required = true, allow_negative_numbers = true)]
+        point: Vec<f64>,
+    },
+    /// Größte zulässige Öffnungsgeschwindigkeit eines Querschnitts suchen und simulieren
+    MaxSpeed {
+        /// Bereich der Öffnungsgeschwindigkeit in m/s
+        #[arg(long, num_args = 2, value_names = ["MIN", "MAX"], default_values_t = [0.0005, 0.0037])]
+        speed: Vec<f64>,
+        /// Breite der Füllquerschnitte in m
+        #[arg(long, default_value_t = 2.3)]
+        width: f64,
+        /// Höhe der Füllquerschnitte in m
+        #[arg(long, default_value_t = 0.35)]
+        height: f64,
+        /// Maximale Füllzeit in s, ein Wert genau auf der Grenze ist zulässig [Standard: Eingabedatei, sonst 1260]
+        #[arg(long)]
+        max_time: Option<f64>,
+        /// Zulässiger Bereich der Durchflusszunahme in m³/s² [Standard: Eingabedatei, sonst -0.7299 0.1962]
+        #[arg(long, num_args = 2, value_names = ["MIN", "MAX"], allow_negative_numbers = true)]
+        discharge_rate: Option<Vec<f64>>,
+        /// Zulässiger Bereich des Durchflusses in m³/s [Standard: Eingabedatei, sonst -1 58.26]
+        #[arg(long, num_args = 2, value_names = ["MIN", "MAX"], allow_negative_numbers = true)]
+        discharge: Option<Vec<f64>>,
+        /// Anzahl der untersuchten Geschwindigkeitsstufen
+        #[arg(long, default_value_t = 1000)]
+        steps: u32,
+    },
+}
+
+#[derive(Args)]
+pub struct Bereiche {
+    /// Bereich der Öffnungsgeschwindigkeit in m/s
+    #[arg(long, num_args = 2, value_names = ["MIN", "MAX"], default_values_t = [0.0005, 0.0037])]
+    pub speed: Vec<f64>,
+    /// Bereich der Querschnittshöhe in m
+    #[arg(long, num_args = 2, value_names = ["MIN", "MAX"], default_values_t = [0.25, 0.35])]
+    pub height: Vec<f64>,
+    /// Bereich der Querschnittsbreite in m
+    #[arg(long, num_args = 2, value_names = ["MIN", "MAX"], default_values_t = [2.0, 2.5])]
+    pub width: Vec<f64>,
+}
+
+/**
+Grenzen der Studien. Ohne Angabe gelten die Nachweise der Eingabedatei,
+fehlen auch dort Füllzeit oder Wasserspiegelneigung, die bisherigen Standardwerte des Befehls.
+Wie bisher gilt ein Wert genau auf der Grenze als verletzt.
+*/
+#[derive(Args)]
+pub struct Grenzen {
+    /// Maximale Füllzeit in s, sie muss unterschritten werden [Standard: Eingabedatei, sonst 1200 bei interaction, keine bei pareto, 1260 bei den übrigen Befehlen]
+    #[arg(long)]
+    pub max_time: Option<f64>,
+    /// Maximale Wasserspiegelneigung in mm/m, sie muss unterschritten werden [Standard: Eingabedatei, sonst 0.35 bei minimize, keine bei pareto, 0.4 bei den übrigen Befehlen]
+    #[arg(long)]
+    pub max_slope: Option<f64>,
+}
+
+// Bisherige Standardgrenzen der Befehle: Füllzeit in s und Wasserspiegelneigung in mm/m
+pub const STANDARDGRENZEN: (f64, f64) = (1260.0, 0.4);
+pub const STANDARDGRENZEN_MINIMIZE: (f64, f64) = (1260.0, 0.35);
+pub const STANDARDGRENZEN_INTERACTION: (f64, f64) = (1200.0, 0.4);
+
+impl Grenzen {
+    pub fn nachweise(&self, eingabe: &Nachweise, standard: (f64, f64)) -> Nachweise {
+        let mut nachweise = eingabe.clone();
+        nachweise.fuellzeit = self.max_time.or(eingabe.fuellzeit).or(Some(standard.0));
+        nachweise.wasserspiegelneigung = self
+            .max_slope
+            .or(eingabe.wasserspiegelneigung)
+            .or(Some(standard.1));
+        nachweise
+    }
+
+    // Nur die angegebenen Grenzen, ohne Standardwerte für fehlende
+    pub fn angegebene_nachweise(&self, eingabe: &Nachweise) -> Nachweise {
+        let mut nachweise = eingabe.clone();
+        nachweise.fuellzeit = self.max_time.or(eingabe.fuellzeit);
+        nachweise.wasserspiegelneigung = self.max_slope.or(eingabe.wasserspiegelneigung);
+        nachweise
+    }
+}
+
+#[derive(Args)]
+pub struct Suchwerte {
+    /// Anzahl der Stufen, mit denen die Grenze zuerst eingegrenzt wird. Zulässige Bereiche, die schmaler
+    /// als eine Stufe (Suchbereich / brackets) sind, können zwischen zwei Stufen übersehen werden
+    #[arg(long, default_value_t = 10)]
+    pub brackets: u32,
+    /// Genauigkeit der Grenze als Anteil des Suchbereichs, größer als 0
+    #[arg(long, default_value_t = 1e-3, value_parser = positiv)]
+    pub tolerance: f64,
+}
+
+impl Suchwerte {
+    pub fn suche(&self) -> Suche {
+        Suche {
+            stufen: self.brackets,
+            toleranz: self.tolerance,
+        }
+    }
+}
+
+// Zahl größer als 0, etwa für Toleranzen
+fn positiv(text: &str) -> Result<f64, String> {
+    match text.parse::<f64>() {
+        Ok(wert) if wert > 0.0 => Ok(wert),
+        Ok(_) => Err(String::from("must be greater than 0")),
+        Err(why) => Err(why.to_string()),
+    }
+}
+
+// Wandelt die zwei Werte eines Bereichsarguments in ein Tupel (min, max)
+pub fn bereich(werte: &[f64]) -> (f64, f64) {
+    (werte[0], werte[1])
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum Trennzeichen {
+    /// Komma
+    Comma,
+    /// Semikolon
+    Semicolon,
+    /// Tabulator
+    Tab,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum Art {
+    /// Polynom, angepasst nach der Methode der kleinsten Quadrate
+    Poly,
+    /// Kubische radiale Basisfunktionen, die die Stützstellen exakt treffen
+    Rbf,
+}
+
+impl Art {
+    pub fn modellart(self) -> Modellart {
+        match self {
+            Art::Poly => Modellart::Polynom,
+            Art::Rbf => Modellart::Rbf,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum Ziel {
+    /// Gesamtfläche der rechteckigen Füllquerschnitte
+    Area,
+    /// Füllzeit
+    Time,
+}
+
+impl Ziel {
+    pub fn zielgroesse(self) -> Zielgroesse {
+        match self {
+            Ziel::Area => Zielgroesse::Flaeche,
+            Ziel::Time => Zielgroesse::Fuellzeit,
+        }
+    }
+}
+
+#[derive(Args)]
+pub struct Optimierer {
+    /// Höchstzahl der Simulationen
+    #[arg(long, default_value_t = 1000)]
+    pub max_evaluations: usize,
+    /// Abbruch, wenn der Simplex kleiner als dieser Anteil der Bereiche ist, größer als 0
+    #[arg(long, default_value_t = 1e-4, value_parser = positiv)]
+    pub tolerance: f64,
+}
+
+impl Optimierer {
+    pub fn nelder_mead(&self) -> NelderMead {
+        NelderMead {
+            max_auswertungen: self.max_evaluations,
+            toleranz: self.tolerance,
+            ..NelderMead::default()
+        }
+    }
+}
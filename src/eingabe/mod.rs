@@ -0,0 +1,689 @@
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs::File;
+use std::io::prelude::*;
+use std::path::Path;
+
+use crate::bruteforce::*;
+use crate::fileio::lies_zahlentabelle;
+use crate::hydraulic::*;
+use crate::nachweis::Nachweise;
+use crate::versuchsplan::{Variation, Versuchsplan};
+use crate::zeitintegration::Zeitintegration;
+use crate::zufall::Verteilung;
+
+/**
+Vollständige Beschreibung einer Schleuse wie sie in der Eingabedatei steht.
+Die Querschnitte des Füll- und Entleerungssystems werden als Liste angegeben:
+
+```toml
+[[fuellsystem]]
+hoehe = 0.0
+startzeit = 0.0
+schuetz = false
+querschnitt = { typ = "rechteck", breite = 2.3, hoehe = 0.35, oeffnungsgeschwindigkeit = 0.0035 }
+verluste = { vorlage = "abgerundet", austritt = 1.0 }
+```
+*/
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Schleusenwerte {
+    pub unterwasser: f64,
+    pub unterwassersohle: f64,
+    pub oberwasser: f64,
+    pub oberwassersohle: f64,
+    pub kanalbreite: f64,
+    pub kammerbreite: f64,
+    pub kammerlaenge: f64,
+    pub fuellsystem: Vec<Querschnittssystemwerte>,
+    #[serde(default)]
+    pub entleersystem: Vec<Querschnittssystemwerte>,
+    #[serde(default)]
+    pub zeitintegration: Zeitintegration,
+    #[serde(default)]
+    pub nachweise: Nachweise,
+    #[serde(default)]
+    pub unsicherheiten: Vec<Unsicherheit>,
+    #[serde(default)]
+    pub versuchsplan: Versuchsplan,
+    #[serde(default)]
+    pub variation: Vec<Variation>,
+}
+
+/**
+Unsichere Eingabe für die Monte-Carlo-Simulation. Der Pfad folgt `parameter::lese_parameter`,
+mit `[*]` erhält jeder Querschnitt der Liste denselben gezogenen Wert:
+
+```toml
+[[unsicherheiten]]
+pfad = "fuellsystem[*].verluste.einlauf"
+verteilung = "normal"
+mittelwert = 0.5
+standardabweichung = 0.05
+```
+*/
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Unsicherheit {
+    pub pfad: String,
+    #[serde(flatten)]
+    pub verteilung: Verteilung,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Querschnittssystemwerte {
+    pub hoehe: f64, // Unterkante des Querschnitts ab Bezugshöhe
+    #[serde(default)]
+    pub startzeit: f64, // In Sekunden
+    #[serde(default)]
+    pub schuetz: bool, // Unterströmung eines Schützes statt Ausfluss aus dem Querschnitt
+    pub querschnitt: Querschnittswerte,
+    #[serde(default)]
+    pub verluste: Verlustwerte,
+}
+
+/**
+Verlustbeiwerte eines Querschnitts. Ausgegangen wird von der `vorlage` (ohne Angabe `standard`),
+einzeln angegebene Beiwerte ersetzen die der Vorlage:
+
+```toml
+verluste = { vorlage = "gebrochen", aufweitung = 1.0, ueberfall = [0.65, -0.05] }
+```
+
+Nicht jeder Querschnitt verwendet alle Beiwerte, siehe `Querschnittswerte::unbenutzte_verluste`.
+Ein angegebener, aber nicht verwendeter Beiwert ist ein Fehler in der Eingabe.
+*/
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Verlustwerte {
+    pub vorlage: Option<Vorlage>,
+    pub einlauf: Option<f64>,
+    pub aufweitung: Option<f64>,
+    pub austritt: Option<f64>,
+    pub ueberfall: Option<Vec<f64>>, // Polynomkoeffizienten in aufsteigenden Potenzen
+    pub kontraktion: Option<f64>,
+    pub umlenkung: Option<f64>,
+}
+
+impl Verlustwerte {
+    pub fn beiwerte(&self) -> Verlustbeiwerte {
+        let vorlage = self.vorlage.unwrap_or(Vorlage::Standard).beiwerte();
+        Verlustbeiwerte {
+            einlauf: self.einlauf.unwrap_or(vorlage.einlauf),
+            aufweitung: self.aufweitung.unwrap_or(vorlage.aufweitung),
+            austritt: self.austritt.unwrap_or(vorlage.austritt),
+            ueberfall: self.ueberfall.clone().unwrap_or(vorlage.ueberfall),
+            kontraktion: self.kontraktion.unwrap_or(vorlage.kontraktion),
+            umlenkung: self.umlenkung.unwrap_or(vorlage.umlenkung),
+        }
+    }
+
+    // Namen der ausdrücklich angegebenen Beiwerte
+    fn angegeben(&self) -> Vec<&'static str> {
+        [
+            ("einlauf", self.einlauf.is_some()),
+            ("aufweitung", self.aufweitung.is_some()),
+            ("austritt", self.austritt.is_some()),
+            ("ueberfall", self.ueberfall.is_some()),
+            ("kontraktion", self.kontraktion.is_some()),
+            ("umlenkung", self.umlenkung.is_some()),
+        ]
+        .into_iter()
+        .filter(|(_, da)| *da)
+        .map(|(name, _)| name)
+        .collect()
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(tag = "typ", rename_all = "lowercase")]
+pub enum Querschnittswerte {
+    Rechteck {
+        breite: f64,
+        hoehe: f64,
+        oeffnungsgeschwindigkeit: f64, // in m/s
+    },
+    /**
+    Rechteck mit stückweise linearem Hubgesetz, als Stützstellen [Zeit in s, Höhe in m]
+    direkt in der Eingabe oder als CSV-Datei mit einer Stützstelle je Zeile, genau eins von beiden:
+
+    ```toml
+    querschnitt = { typ = "rechteckgesetz", breite = 2.3, hoehe = 0.35, oeffnung = [[0.0, 0.0], [60.0, 0.05], [120.0, 0.05], [180.0, 0.35]] }
+    querschnitt = { typ = "rechteckgesetz", breite = 2.3, hoehe = 0.35, oeffnung_csv = "schuetzweg.csv" }
+    ```
+    */
+    RechteckGesetz {
+        breite: f64,
+        hoehe: f64,
+        #[serde(default)]
+        oeffnung: Vec<[f64; 2]>,
+        #[serde(default)]
+        oeffnung_csv: Option<String>, // Relativ zur Eingabedatei
+    },
+    // Rohrschieber in einem kreisrunden Querschnitt
+    Kreis {
+        durchmesser: f64,
+        oeffnungsgeschwindigkeit: f64, // in m/s
+    },
+    // Zylinderschütz mit Fenster über den ganzen Umfang
+    Zylinder {
+        durchmesser: f64,
+        hub: f64,
+        oeffnungsgeschwindigkeit: f64, // in m/s
+    },
+    // Segmentschütz, Drehpunkthöhe ab Schwelle
+    Segment {
+        breite: f64,
+        hoehe: f64,
+        radius: f64,
+        drehpunkthoehe: f64,
+        oeffnungsgeschwindigkeit: f64, // in m/s
+    },
+}
+
+impl Querschnittswerte {
+    /**
+    Verlustbeiwerte, die in die Berechnung dieses Querschnitts nicht eingehen. Kreis und Segment
+    kennen keine Aufweitung im Querschnitt, nur das Rechteck einen Überfall, der Zylinder
+    ersetzt den Einlauf durch die Umlenkung und das Segment bestimmt seine Einschnürung aus dem Winkel.
+    Beim Rechteck geht die Einschnürung nur unter einem Schütz (`schuetz`) ein, unter einem Schütz
+    wirken alle übrigen Beiwerte über den Abfluss des vollständig geöffneten Querschnitts.
+    */
+    pub fn unbenutzte_verluste(&self, schuetz: bool) -> &'static [&'static str] {
+        match self {
+            Querschnittswerte::Rechteck { .. } | Querschnittswerte::RechteckGesetz { .. } => {
+                if schuetz {
+                    &["umlenkung"]
+                } else {
+                    &["kontraktion", "umlenkung"]
+                }
+            }
+            Querschnittswerte::Kreis { .. } => &["aufweitung", "ueberfall", "umlenkung"],
+            Querschnittswerte::Zylinder { .. } => &["einlauf", "aufweitung", "ueberfall"],
+            Querschnittswerte::Segment { .. } => {
+                &["aufweitung", "ueberfall", "kontraktion", "umlenkung"]
+            }
+        }
+    }
+
+    pub fn erschaffe_querschnitt(&self, verluste: Verlustbeiwerte) -> Box<dyn Fuellquerschnitt> {
+        match self {
+            Querschnittswerte::Rechteck {
+                breite,
+                hoehe,
+                oeffnungsgeschwindigkeit,
+            } => Box::new(FuellRechteck {
+                oeffnungsgeschwindigkeit: *oeffnungsgeschwindigkeit,
+                breite: *breite,
+                hoehe: *hoehe,
+                verluste,
+            }),
+            Querschnittswerte::RechteckGesetz {
+                breite,
+                hoehe,
+                oeffnung,
+                ..
+            } => Box::new(FuellRechteckGesetz {
+                breite: *breite,
+                hoehe: *hoehe,
+                stuetzstellen: oeffnung.iter().map(|p| (p[0], p[1])).collect(),
+                verluste,
+            }),
+            Querschnittswerte::Kreis {
+                durchmesser,
+                oeffnungsgeschwindigkeit,
+            } => Box::new(FuellKreis {
+                oeffnungsgeschwindigkeit: *oeffnungsgeschwindigkeit,
+                durchmesser: *durchmesser,
+                verluste,
+            }),
+            Querschnittswerte::Zylinder {
+                durchmesser,
+                hub,
+                oeffnungsgeschwindigkeit,
+            } => Box::new(FuellZylinder {
+                oeffnungsgeschwindigkeit: *oeffnungsgeschwindigkeit,
+                durchmesser: *durchmesser,
+                hub: *hub,
+                verluste,
+            }),
+            Querschnittswerte::Segment {
+                breite,
+                hoehe,
+                radius,
+                drehpunkthoehe,
+                oeffnungsgeschwindigkeit,
+            } => Box::new(FuellSegment {
+                oeffnungsgeschwindigkeit: *oeffnungsgeschwindigkeit,
+                breite: *breite,
+                hoehe: *hoehe,
+                radius: *radius,
+                drehpunkthoehe: *drehpunkthoehe,
+                verluste,
+            }),
+        }
+    }
+}
+
+impl Querschnittssystemwerte {
+    // Prüft, ob alle angegebenen Verlustbeiwerte zum Querschnitt passen
+    fn pruefe_verluste(&self) -> Result<(), String> {
+        let unbenutzt = self.querschnitt.unbenutzte_verluste(self.schuetz);
+        if let Some(name) = self
+            .verluste
+            .angegeben()
+            .into_iter()
+            .find(|name| unbenutzt.contains(name))
+        {
+            return Err(format!(
+                "verluste.{} is not used by this cross-section type and gate setting",
+                name
+            ));
+        }
+        if matches!(&self.verluste.ueberfall, Some(polynom) if polynom.is_empty()) {
+            return Err(String::from(
+                "verluste.ueberfall needs at least one coefficient",
+            ));
+        }
+        Ok(())
+    }
+}
+
+fn erschaffe_fuellsystem(werte: &[Querschnittssystemwerte]) -> Fuellsystem {
+    Fuellsystem {
+        querschnitte: werte
+            .iter()
+            .map(|q| Fuellquerschnittssystem {
+                hoehe: q.hoehe,
+                startzeit: q.startzeit,
+                schuetz: q.schuetz,
+                fuellquerschnitt: q.querschnitt.erschaffe_querschnitt(q.verluste.beiwerte()),
+            })
+            .collect(),
+    }
+}
+
+impl Schleusenwerte {
+    pub fn erschaffe_schleuse(&self) -> Schleuse {
+        Schleuse {
+            kammer: Schleusenkammer {
+                breite: self.kammerbreite,
+                laenge: self.kammerlaenge,
+            },
+            oberhaupt: Oberhaupt {
+                oberwasser: self.oberwasser,
+                oberwasserbreite: self.kanalbreite,
+                oberwassersohle: self.oberwassersohle,
+            },
+            unterhaupt: Unterhaupt {
+                unterwasser: self.unterwasser,
+                unterwasserbreite: self.kanalbreite,
+                unterwassersohle: self.unterwassersohle,
+                entleersystem: erschaffe_fuellsystem(&self.entleersystem),
+            },
+            fuellsystem: erschaffe_fuellsystem(&self.fuellsystem),
+            zeitintegration: self.zeitintegration.clone(),
+        }
+    }
+
+    /**
+    Gibt eine Kopie zurück, in der alle rechteckigen Querschnitte des Füllsystems
+    die angegebenen Abmessungen und Öffnungsgeschwindigkeit erhalten.
+    Höhenlage und Startzeit der einzelnen Querschnitte bleiben erhalten, ebenso Rechtecke mit
+    Hubgesetz, siehe `pruefe_rechteckstudie`.
+    */
+    pub fn mit_rechteck(&self, hoehe: f64, breite: f64, oeffnungsgeschwindigkeit: f64) -> Self {
+        let mut werte = self.clone();
+        for q in &mut werte.fuellsystem {
+            if let Querschnittswerte::Rechteck { .. } = q.querschnitt {
+                q.querschnitt = Querschnittswerte::Rechteck {
+                    breite,
+                    hoehe,
+                    oeffnungsgeschwindigkeit,
+                };
+            }
+        }
+        werte
+    }
+
+    /**
+    Prüft, ob eine Studie über `mit_rechteck` das Füllsystem verändern kann. Ohne rechteckigen
+    Querschnitt ist das ein Fehler, sonst wird die Anzahl der Rechtecke mit Hubgesetz zurückgegeben,
+    die unverändert bleiben.
+    */
+    pub fn pruefe_rechteckstudie(&self) -> Result<usize, String> {
+        if self.anzahl_rechtecke() == 0 {
+            return Err(String::from(
+                "the filling system has no rectangular cross-section the study could vary",
+            ));
+        }
+        Ok(self
+            .fuellsystem
+            .iter()
+            .filter(|q| matches!(q.querschnitt, Querschnittswerte::RechteckGesetz { .. }))
+            .count())
+    }
+
+    // Anzahl der rechteckigen Querschnitte des Füllsystems, die `mit_rechteck` verändert
+    pub fn anzahl_rechtecke(&self) -> usize {
+        self.fuellsystem
+            .iter()
+            .filter(|q| matches!(q.querschnitt, Querschnittswerte::Rechteck { .. }))
+            .count()
+    }
+
+    /**
+    Prüft Verlustbeiwerte und Hubgesetze aller Querschnitte sowie die Verteilungen der Unsicherheiten
+    wie beim Lesen der Eingabedatei
+    und sortiert die Stützstellen der Hubgesetze nach der Zeit. `herkunft` steht in Fehlermeldungen.
+    */
+    pub fn pruefe(&mut self, herkunft: &str) -> Result<(), Eingabefehler> {
+        for (system, liste) in [
+            ("fuellsystem", &mut self.fuellsystem),
+            ("entleersystem", &mut self.entleersystem),
+        ] {
+            for (index, q) in liste.iter_mut().enumerate() {
+                if let Err(why) = q.pruefe_verluste() {
+                    return Err(ungueltig(
+                        format!("{}[{}] in {}", system, index, herkunft),
+                        why,
+                    ));
+                }
+                if let Querschnittswerte::RechteckGesetz { oeffnung, .. } = &mut q.querschnitt {
+                    let ort = |stelle| {
+                        format!(
+                            "{}[{}].querschnitt.oeffnung[{}] in {}",
+                            system, index, stelle, herkunft
+                        )
+                    };
+                    let stellen = oeffnung.iter().copied().enumerate().collect();
+                    *oeffnung = ordne_stuetzstellen(stellen, &ort)?;
+                }
+            }
+        }
+        for (index, u) in self.unsicherheiten.iter().enumerate() {
+            if let Err(why) = u.verteilung.pruefe() {
+                return Err(ungueltig(
+                    format!("unsicherheiten[{}] in {}", index, herkunft),
+                    why,
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /**
+    Prüfsumme (FNV-1a) über alle Eingaben, für gleiche Eingaben auch in verschiedenen Programmläufen gleich.
+    Grundlage ist JSON, da TOML etwa einen `versuchsplan.startwert` über `i64::MAX` nicht darstellen kann.
+    */
+    pub fn fingerabdruck(&self) -> u64 {
+        let text = match serde_json::to_string(self) {
+            Ok(text) => text,
+            Err(why) => panic!("Couldn't serialize the input for its fingerprint: {}", why),
+        };
+        text.bytes().fold(0xcbf29ce484222325, |h, b| {
+            (h ^ b as u64).wrapping_mul(0x100000001b3)
+        })
+    }
+}
+
+/**
+Fehler beim Lesen der Eingabedatei oder einer CSV-Datei, auf die sie verweist.
+*/
+#[derive(Debug)]
+pub enum Eingabefehler {
+    // Die Datei lässt sich nicht öffnen oder lesen
+    Datei { datei: String, grund: String },
+    Toml(toml::de::Error),
+    // Fehler in einer CSV-Datei der Eingabe, mit Datei und Zeile im Text
+    Tabelle(String),
+    // Ungültiger Wert, `ort` nennt Datei und Eintrag bzw. Zeile
+    Ungueltig { ort: String, grund: String },
+}
+
+impl fmt::Display for Eingabefehler {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Eingabefehler::Datei { datei, grund } => {
+                write!(f, "couldn't read {}: {}", datei, grund)
+            }
+            Eingabefehler::Toml(why) => write!(f, "{}", why),
+            Eingabefehler::Tabelle(why) => write!(f, "{}", why),
+            Eingabefehler::Ungueltig { ort, grund } => write!(f, "invalid {}: {}", ort, grund),
+        }
+    }
+}
+
+impl std::error::Error for Eingabefehler {}
+
+impl From<toml::de::Error> for Eingabefehler {
+    fn from(why: toml::de::Error) -> Self {
+        Eingabefehler::Toml(why)
+    }
+}
+
+fn ungueltig(ort: String, grund: String) -> Eingabefehler {
+    Eingabefehler::Ungueltig { ort, grund }
+}
+
+pub fn read_schleusenwerte(file_name: &str) -> Result<Schleusenwerte, Eingabefehler> {
+    // Ein wenig File IO
+    let path = Path::new(file_name);
+    let datei_fehler = |why: std::io::Error| Eingabefehler::Datei {
+        datei: file_name.to_string(),
+        grund: why.to_string(),
+    };
+    let mut file = File::open(path).map_err(datei_fehler)?;
+    // Beim Lesen kann auch viel schief gehen
+    let mut s = String::new();
+    file.read_to_string(&mut s).map_err(datei_fehler)?;
+    // Und beim Parsen erst...
+    let mut contents: Schleusenwerte = toml::from_str(&s)?;
+    let basis = path.parent().unwrap_or_else(|| Path::new(""));
+    for (index, q) in contents
+        .fuellsystem
+        .iter_mut()
+        .chain(contents.entleersystem.iter_mut())
+        .enumerate()
+    {
+        if let Querschnittswerte::RechteckGesetz {
+            oeffnung,
+            oeffnung_csv: Some(datei),
+            ..
+        } = &mut q.querschnitt
+        {
+            if !oeffnung.is_empty() {
+                return Err(ungueltig(
+                    format!("cross-section {} in {}", index, file_name),
+                    String::from("give either oeffnung or oeffnung_csv, not both"),
+                ));
+            }
+            *oeffnung = read_stuetzstellen(&basis.join(datei))?;
+        }
+    }
+    contents.pruefe(file_name)?;
+    let systeme = [
+        ("fuellsystem", &contents.fuellsystem),
+        ("entleersystem", &contents.entleersystem),
+    ];
+    for (system, index, q) in systeme
+        .iter()
+        .flat_map(|(system, liste)| liste.iter().enumerate().map(move |(i, q)| (system, i, q)))
+    {
+        if let Querschnittswerte::RechteckGesetz {
+            oeffnung,
+            oeffnung_csv,
+            ..
+        } = &q.querschnitt
+        {
+            let abnehmend: Vec<f64> = oeffnung
+                .windows(2)
+                .filter(|paar| paar[1][1] < paar[0][1])
+                .map(|paar| paar[1][0])
+                .collect();
+            if let Some(zeit) = abnehmend.first() {
+                warn!(
+                    "{}[{}] in {}: the opening decreases at {} support point(s), first at {} s, used as given",
+                    system,
+                    index,
+                    oeffnung_csv.as_deref().unwrap_or(file_name),
+                    abnehmend.len(),
+                    zeit
+                );
+            }
+        }
+    }
+    Ok(contents)
+}
+
+/**
+Liest Stützstellen (Zeit, Höhe) aus einer CSV-Datei, siehe `fileio::lies_tabelle`.
+*/
+fn read_stuetzstellen(path: &Path) -> Result<Vec<[f64; 2]>, Eingabefehler> {
+    let tabelle = lies_zahlentabelle(path).map_err(Eingabefehler::Tabelle)?;
+    let ort = |nummer| format!("line {} of {}", nummer, path.display());
+    let mut stellen = Vec::new();
+    for (nummer, zeile) in tabelle.zeilennummern.iter().zip(&tabelle.zeilen) {
+        match zeile.as_slice() {
+            [zeit, hoehe, ..] => stellen.push((*nummer, [*zeit, *hoehe])),
+            _ => {
+                return Err(ungueltig(
+                    ort(*nummer),
+                    String::from("need time and height"),
+                ))
+            }
+        }
+    }
+    if stellen.is_empty() {
+        return Err(ungueltig(
+            path.display().to_string(),
+            String::from("no support points"),
+        ));
+    }
+    ordne_stuetzstellen(stellen, &ort)
+}
+
+/**
+Sortiert Stützstellen [Zeit, Höhe] nach der Zeit. Jede Stützstelle trägt ihre Zeile bzw. ihren Index
+in der Eingabe, aus dem `ort` die Angabe für eine Fehlermeldung bildet. Ein leeres Hubgesetz, doppelte
+Zeiten und negative Höhen sind Fehler. Eine abnehmende Höhe, etwa durch Messrauschen in einem
+aufgezeichneten Schützweg, wird so übernommen; `read_schleusenwerte` warnt davor.
+*/
+fn ordne_stuetzstellen(
+    mut stellen: Vec<(usize, [f64; 2])>,
+    ort: &dyn Fn(usize) -> String,
+) -> Result<Vec<[f64; 2]>, Eingabefehler> {
+    if stellen.is_empty() {
+        return Err(ungueltig(
+            ort(0),
+            String::from("the opening law needs at least one support point"),
+        ));
+    }
+    stellen.sort_by(|(_, a), (_, b)| a[0].total_cmp(&b[0]));
+    if let Some((nummer, [zeit, hoehe])) = stellen.iter().find(|(_, [_, hoehe])| *hoehe < 0.0) {
+        return Err(ungueltig(
+            ort(*nummer),
+            format!("negative height {} at {} s", hoehe, zeit),
+        ));
+    }
+    for paar in stellen.windows(2) {
+        let ((_, [zeit0, _]), (nummer, [zeit1, _])) = (paar[0], paar[1]);
+        if zeit1 == zeit0 {
+            return Err(ungueltig(
+                ort(nummer),
+                format!("duplicate time {} s", zeit1),
+            ));
+        }
+    }
+    Ok(stellen.into_iter().map(|(_, stelle)| stelle).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ort(index: usize) -> String {
+        format!("oeffnung[{}]", index)
+    }
+
+    #[test]
+    fn stuetzstellen_werden_sortiert_und_geprueft() {
+        let stellen = vec![(0, [60.0, 0.35]), (1, [0.0, 0.0]), (2, [30.0, 0.4])];
+        // Abnehmende Höhen werden übernommen
+        let geordnet = ordne_stuetzstellen(stellen, &ort).unwrap();
+        assert_eq!(geordnet, vec![[0.0, 0.0], [30.0, 0.4], [60.0, 0.35]]);
+        assert!(ordne_stuetzstellen(Vec::new(), &ort).is_err());
+        assert!(ordne_stuetzstellen(vec![(0, [0.0, -0.1])], &ort).is_err());
+        let doppelt = vec![(0, [0.0, 0.0]), (1, [0.0, 0.1])];
+        match ordne_stuetzstellen(doppelt, &ort) {
+            Err(Eingabefehler::Ungueltig { ort, .. }) => assert_eq!(ort, "oeffnung[1]"),
+            sonst => panic!("{:?}", sonst),
+        }
+    }
+
+    #[test]
+    fn fehlerhafte_hubgesetze_werden_gemeldet() {
+        let eingabe = std::fs::read_to_string("test.toml").unwrap();
+        let ordner = std::env::temp_dir().join(format!("eingabe_test_{}", std::process::id()));
+        std::fs::create_dir_all(&ordner).unwrap();
+        let pruefe = |querschnitt: &str| {
+            let datei = ordner.join("eingabe.toml");
+            let mut ersetzt = false;
+            let zeilen: Vec<String> = eingabe
+                .lines()
+                .map(|zeile| {
+                    if !ersetzt && zeile.trim_start().starts_with("querschnitt") {
+                        ersetzt = true;
+                        format!("querschnitt = {}", querschnitt)
+                    } else {
+                        zeile.to_string()
+                    }
+                })
+                .collect();
+            std::fs::write(&datei, zeilen.join("\n")).unwrap();
+            read_schleusenwerte(datei.to_str().unwrap())
+        };
+        let gesetz = |angabe: &str| {
+            format!(
+                "{{ typ = \"rechteckgesetz\", breite = 2.3, hoehe = 0.35, {} }}",
+                angabe
+            )
+        };
+        assert!(pruefe(&gesetz("oeffnung = [[0.0, 0.0], [60.0, 0.35]]")).is_ok());
+        assert!(matches!(
+            pruefe(&gesetz("oeffnung = []")),
+            Err(Eingabefehler::Ungueltig { .. })
+        ));
+        assert!(matches!(
+            pruefe(&gesetz(
+                "oeffnung = [[0.0, 0.0]], oeffnung_csv = \"weg.csv\""
+            )),
+            Err(Eingabefehler::Ungueltig { .. })
+        ));
+        assert!(matches!(
+            pruefe(&gesetz("oeffnung_csv = \"fehlt.csv\"")),
+            Err(Eingabefehler::Tabelle(_))
+        ));
+        std::fs::write(ordner.join("weg.csv"), "zeit,hoehe\n0.0,0.0\n60.0\n").unwrap();
+        assert!(matches!(
+            pruefe(&gesetz("oeffnung_csv = \"weg.csv\"")),
+            Err(Eingabefehler::Ungueltig { .. })
+        ));
+        std::fs::write(ordner.join("weg.csv"), "zeit,hoehe\n0.0,0.0\n60.0,x\n").unwrap();
+        assert!(matches!(
+            pruefe(&gesetz("oeffnung_csv = \"weg.csv\"")),
+            Err(Eingabefehler::Tabelle(_))
+        ));
+        std::fs::remove_dir_all(&ordner).unwrap();
+    }
+
+    #[test]
+    fn fingerabdruck_unterscheidet_grosse_startwerte() {
+        let mut werte = read_schleusenwerte("test.toml").unwrap();
+        let klein = werte.fingerabdruck();
+        werte.versuchsplan.startwert = u64::MAX;
+        let gross = werte.fingerabdruck();
+        werte.versuchsplan.startwert = u64::MAX - 1;
+        assert_ne!(klein, gross);
+        assert_ne!(gross, werte.fingerabdruck());
+    }
+}
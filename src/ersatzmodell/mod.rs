@@ -0,0 +1,344 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum Modellart {
+    // Polynom vom Grad `grad` in allen Eingängen, angepasst nach der Methode der kleinsten Quadrate
+    Polynom,
+    // Interpolation mit kubischen radialen Basisfunktionen und linearem Polynom
+    Rbf,
+}
+
+/**
+Ersatzmodell (Antwortfläche) für eine Ergebnisgröße einer Studie, etwa die Füllzeit,
+als Funktion der Eingänge, etwa Höhe, Breite und Öffnungsgeschwindigkeit.
+Die Eingänge werden auf den Bereich der Stützstellen normiert, sodass die Auswertung
+außerhalb dieses Bereichs eine Extrapolation ist.
+Das Modell lässt sich als TOML speichern und ohne neue Simulation auswerten.
+*/
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Ersatzmodell {
+    pub name: String,
+    pub art: Modellart,
+    pub grad: u32,
+    // Bereich der Eingänge in den Stützstellen
+    pub untere: Vec<f64>,
+    pub obere: Vec<f64>,
+    // Exponenten der Eingänge je Polynomglied und die zugehörigen Koeffizienten
+    pub exponenten: Vec<Vec<u32>>,
+    pub koeffizienten: Vec<f64>,
+    // Nur RBF: normierte Stützstellen und ihre Gewichte
+    #[serde(default)]
+    pub zentren: Vec<Vec<f64>>,
+    #[serde(default)]
+    pub gewichte: Vec<f64>,
+}
+
+// Güte einer Anpassung, `kreuzvalidierung` ist der RMSE der k-fachen Kreuzvalidierung
+#[derive(Clone, Copy, Debug)]
+pub struct Guete {
+    pub bestimmtheitsmass: f64,
+    pub rmse: f64,
+    pub kreuzvalidierung: f64,
+}
+
+impl Ersatzmodell {
+    /**
+    Passt ein Modell an die Stützstellen `x` mit den Werten `y` an.
+    Beim RBF-Modell wird `grad` ignoriert, die Stützstellen werden exakt getroffen.
+    */
+    pub fn anpassen(
+        name: &str,
+        art: Modellart,
+        grad: u32,
+        x: &[Vec<f64>],
+        y: &[f64],
+    ) -> Result<Ersatzmodell, String> {
+        let dimension = match x.first() {
+            Some(punkt) => punkt.len(),
+            None => return Err("no data points".to_string()),
+        };
+        if x.len() != y.len() || x.iter().any(|p| p.len() != dimension) {
+            return Err("data points of different length".to_string());
+        }
+        let untere: Vec<f64> = (0..dimension)
+            .map(|i| x.iter().map(|p| p[i]).fold(f64::INFINITY, f64::min))
+            .collect();
+        let obere: Vec<f64> = (0..dimension)
+            .map(|i| x.iter().map(|p| p[i]).fold(f64::NEG_INFINITY, f64::max))
+            .collect();
+        let mut modell = Ersatzmodell {
+            name: name.to_string(),
+            art,
+            grad,
+            untere,
+            obere,
+            exponenten: polynomglieder(
+                dimension,
+                match art {
+                    Modellart::Polynom => grad,
+                    Modellart::Rbf => 1,
+                },
+            ),
+            koeffizienten: Vec::new(),
+            zentren: Vec::new(),
+            gewichte: Vec::new(),
+        };
+        let normiert: Vec<Vec<f64>> = x.iter().map(|p| modell.normiere(p)).collect();
+        let glieder = modell.exponenten.len();
+        if normiert.len() < glieder {
+            return Err(format!(
+                "{} data points are not enough for {} polynomial terms",
+                normiert.len(),
+                glieder
+            ));
+        }
+        let p: Vec<Vec<f64>> = normiert
+            .iter()
+            .map(|u| auswerte_glieder(&modell.exponenten, u))
+            .collect();
+
+        match art {
+            Modellart::Polynom => {
+                // Normalgleichungen P^T P c = P^T y
+                let a = (0..glieder)
+                    .map(|i| {
+                        (0..glieder)
+                            .map(|j| p.iter().map(|z| z[i] * z[j]).sum())
+                            .collect()
+                    })
+                    .collect();
+                let b = (0..glieder)
+                    .map(|i| p.iter().zip(y).map(|(z, w)| z[i] * w).sum())
+                    .collect();
+                modell.koeffizienten = loese(a, b)?;
+            }
+            Modellart::Rbf => {
+                // [Phi P; P^T 0] [w; c] = [y; 0]
+                let n = normiert.len();
+                let mut a = vec![vec![0.0; n + glieder]; n + glieder];
+                for i in 0..n {
+                    for j in 0..n {
+                        a[i][j] = basis(abstand(&normiert[i], &normiert[j]));
+                    }
+                    for k in 0..glieder {
+                        a[i][n + k] = p[i][k];
+                        a[n + k][i] = p[i][k];
+                    }
+                }
+                let mut b = y.to_vec();
+                b.resize(n + glieder, 0.0);
+                let mut loesung = loese(a, b)?;
+                modell.koeffizienten = loesung.split_off(n);
+                modell.gewichte = loesung;
+                modell.zentren = normiert;
+            }
+        }
+        Ok(modell)
+    }
+
+    /**
+    Wert des Modells am Punkt `punkt`, die Eingänge in derselben Reihenfolge wie bei der Anpassung.
+    */
+    pub fn auswerten(&self, punkt: &[f64]) -> f64 {
+        let u = self.normiere(punkt);
+        let polynom: f64 = auswerte_glieder(&self.exponenten, &u)
+            .iter()
+            .zip(&self.koeffizienten)
+            .map(|(g, c)| g * c)
+            .sum();
+        let radial: f64 = self
+            .zentren
+            .iter()
+            .zip(&self.gewichte)
+            .map(|(z, w)| w * basis(abstand(z, &u)))
+            .sum();
+        polynom + radial
+    }
+
+    // Ob `punkt` im Bereich der Stützstellen liegt
+    pub fn im_bereich(&self, punkt: &[f64]) -> bool {
+        punkt
+            .iter()
+            .zip(self.untere.iter().zip(&self.obere))
+            .all(|(x, (u, o))| (u..=o).contains(&x))
+    }
+
+    // Bildet die Eingänge auf [-1, 1] ab
+    fn normiere(&self, punkt: &[f64]) -> Vec<f64> {
+        punkt
+            .iter()
+            .zip(self.untere.iter().zip(&self.obere))
+            .map(|(x, (u, o))| {
+                if o > u {
+                    2.0 * (x - u) / (o - u) - 1.0
+                } else {
+                    0.0
+                }
+            })
+            .collect()
+    }
+}
+
+/**
+Güte der Anpassung: Bestimmtheitsmaß und RMSE an den Stützstellen sowie der RMSE einer
+`teile`-fachen Kreuzvalidierung, bei der jeder Punkt von einem Modell ohne ihn vorhergesagt wird.
+Da das RBF-Modell die Stützstellen exakt trifft, ist bei ihm nur die Kreuzvalidierung aussagekräftig.
+*/
+pub fn guete(
+    modell: &Ersatzmodell,
+    x: &[Vec<f64>],
+    y: &[f64],
+    teile: usize,
+) -> Result<Guete, String> {
+    let n = y.len();
+    let mittel = y.iter().sum::<f64>() / n as f64;
+    let quadratsumme = y.iter().map(|w| (w - mittel).powi(2)).sum::<f64>();
+    let residuen = x
+        .iter()
+        .zip(y)
+        .map(|(p, w)| (modell.auswerten(p) - w).powi(2))
+        .sum::<f64>();
+
+    let teile = teile.clamp(2, n.max(2));
+    let mut kreuz = 0.0;
+    for teil in 0..teile {
+        let (lern, pruef): (Vec<usize>, Vec<usize>) = (0..n).partition(|i| i % teile != teil);
+        let lern_x: Vec<Vec<f64>> = lern.iter().map(|&i| x[i].clone()).collect();
+        let lern_y: Vec<f64> = lern.iter().map(|&i| y[i]).collect();
+        let teilmodell =
+            Ersatzmodell::anpassen(&modell.name, modell.art, modell.grad, &lern_x, &lern_y)?;
+        kreuz += pruef
+            .iter()
+            .map(|&i| (teilmodell.auswerten(&x[i]) - y[i]).powi(2))
+            .sum::<f64>();
+    }
+
+    Ok(Guete {
+        bestimmtheitsmass: 1.0 - residuen / quadratsumme,
+        rmse: (residuen / n as f64).sqrt(),
+        kreuzvalidierung: (kreuz / n as f64).sqrt(),
+    })
+}
+
+// Alle Exponenten mit Summe höchstens `grad`, beginnend mit dem konstanten Glied
+fn polynomglieder(dimension: usize, grad: u32) -> Vec<Vec<u32>> {
+    let mut glieder = vec![Vec::new()];
+    for _ in 0..dimension {
+        glieder = glieder
+            .into_iter()
+            .flat_map(|g: Vec<u32>| {
+                let rest = grad - g.iter().sum::<u32>();
+                (0..=rest).map(move |e| {
+                    let mut g = g.clone();
+                    g.push(e);
+                    g
+                })
+            })
+            .collect();
+    }
+    glieder.sort_by_key(|g| g.iter().sum::<u32>());
+    glieder
+}
+
+fn auswerte_glieder(exponenten: &[Vec<u32>], u: &[f64]) -> Vec<f64> {
+    exponenten
+        .iter()
+        .map(|e| u.iter().zip(e).map(|(x, &k)| x.powi(k as i32)).product())
+        .collect()
+}
+
+fn basis(r: f64) -> f64 {
+    r * r * r
+}
+
+fn abstand(a: &[f64], b: &[f64]) -> f64 {
+    a.iter()
+        .zip(b)
+        .map(|(x, y)| (x - y).powi(2))
+        .sum::<f64>()
+        .sqrt()
+}
+
+// Gauß-Elimination mit Spaltenpivotsuche
+fn loese(mut a: Vec<Vec<f64>>, mut b: Vec<f64>) -> Result<Vec<f64>, String> {
+    let n = b.len();
+    let skala = a
+        .iter()
+        .flatten()
+        .fold(0.0_f64, |m, w| m.max(w.abs()))
+        .max(f64::MIN_POSITIVE);
+    for k in 0..n {
+        let pivot = (k..n)
+            .max_by(|&i, &j| a[i][k].abs().total_cmp(&a[j][k].abs()))
+            .unwrap();
+        if a[pivot][k].abs() <= 1e-12 * skala {
+            return Err("singular system, duplicate points or too few distinct values".to_string());
+        }
+        a.swap(k, pivot);
+        b.swap(k, pivot);
+        let (oben, unten) = a.split_at_mut(k + 1);
+        let pivotzeile = &oben[k];
+        for (i, zeile) in unten.iter_mut().enumerate() {
+            let faktor = zeile[k] / pivotzeile[k];
+            if faktor == 0.0 {
+                continue;
+            }
+            for (w, p) in zeile[k..].iter_mut().zip(&pivotzeile[k..]) {
+                *w -= faktor * p;
+            }
+            b[k + 1 + i] -= faktor * b[k];
+        }
+    }
+    let mut x = vec![0.0; n];
+    for k in (0..n).rev() {
+        let summe: f64 = (k + 1..n).map(|j| a[k][j] * x[j]).sum();
+        x[k] = (b[k] - summe) / a[k][k];
+    }
+    Ok(x)
+}
+
+// Mehrere Ersatzmodelle über denselben Eingängen, so wie sie als TOML gespeichert werden
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Ersatzmodelle {
+    pub eingaenge: Vec<String>,
+    pub modelle: Vec<Ersatzmodell>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn raster() -> Vec<Vec<f64>> {
+        let mut x = Vec::new();
+        for i in 0..5 {
+            for j in 0..4 {
+                x.push(vec![1.0 + 0.5 * i as f64, -2.0 + 1.5 * j as f64]);
+            }
+        }
+        x
+    }
+
+    #[test]
+    fn polynom_grad_2_gibt_quadratische_funktion_wieder() {
+        let f = |p: &[f64]| {
+            1.0 + 2.0 * p[0] - p[1] + 0.5 * p[0] * p[0] + 3.0 * p[0] * p[1] - p[1] * p[1]
+        };
+        let x = raster();
+        let y: Vec<f64> = x.iter().map(|p| f(p)).collect();
+        let modell = Ersatzmodell::anpassen("f", Modellart::Polynom, 2, &x, &y).unwrap();
+        for punkt in [[1.3, -1.1], [2.9, 2.4], [0.5, 3.0]] {
+            assert!((modell.auswerten(&punkt) - f(&punkt)).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn rbf_trifft_stuetzstellen() {
+        let x = raster();
+        let y: Vec<f64> = x.iter().map(|p| (p[0] * p[1]).sin() + p[0]).collect();
+        let modell = Ersatzmodell::anpassen("f", Modellart::Rbf, 0, &x, &y).unwrap();
+        for (punkt, wert) in x.iter().zip(&y) {
+            assert!((modell.auswerten(punkt) - wert).abs() < 1e-9);
+        }
+    }
+}
@@ -0,0 +1,307 @@
+use std::fs::File;
+use std::io::prelude::*;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+
+use log::{error, info};
+use serde::Serialize;
+
+/**
+Zahlenformat der Ausgabetabellen. Mit `dezimalkomma` werden Zahlen wie `1,5` geschrieben,
+das Trennzeichen darf dann kein Komma sein.
+*/
+#[derive(Clone, Copy, Debug)]
+pub struct Format {
+    pub trennzeichen: char,
+    pub dezimalkomma: bool,
+    pub kopfzeile: bool,
+}
+
+impl Default for Format {
+    fn default() -> Self {
+        Format {
+            trennzeichen: ',',
+            dezimalkomma: false,
+            kopfzeile: true,
+        }
+    }
+}
+
+impl Format {
+    fn zahl(&self, wert: f64) -> String {
+        let text = wert.to_string();
+        if self.dezimalkomma {
+            text.replace('.', ",")
+        } else {
+            text
+        }
+    }
+}
+
+// Eintrag einer Tabelle, Zahlen werden nach dem `Format` geschrieben
+#[derive(Clone, Debug)]
+pub enum Zelle {
+    Zahl(f64),
+    Text(String),
+}
+
+impl From<f64> for Zelle {
+    fn from(wert: f64) -> Self {
+        Zelle::Zahl(wert)
+    }
+}
+
+impl From<&str> for Zelle {
+    fn from(text: &str) -> Self {
+        Zelle::Text(text.to_string())
+    }
+}
+
+/**
+Ergebnistabelle einer Studie mit Spaltennamen und Einheiten. Die Kopfzeile lautet
+`name [einheit]`, bei einheitenlosen Spalten nur `name`.
+*/
+#[derive(Clone, Debug, Default)]
+pub struct Tabelle {
+    kopf: Vec<String>,
+    zeilen: Vec<Vec<Zelle>>,
+}
+
+impl Tabelle {
+    pub fn neu<'a>(spalten: impl IntoIterator<Item = (&'a str, &'a str)>) -> Self {
+        Tabelle {
+            kopf: spalten
+                .into_iter()
+                .map(|(name, einheit)| {
+                    if einheit.is_empty() {
+                        name.to_string()
+                    } else {
+                        format!("{} [{}]", name, einheit)
+                    }
+                })
+                .collect(),
+            zeilen: Vec::new(),
+        }
+    }
+
+    pub fn zeile<Z: Into<Zelle>>(&mut self, werte: impl IntoIterator<Item = Z>) {
+        self.zeilen
+            .push(werte.into_iter().map(Into::into).collect());
+    }
+
+    pub fn ist_leer(&self) -> bool {
+        self.zeilen.is_empty()
+    }
+
+    pub fn text(&self, format: &Format) -> String {
+        let trenner = format.trennzeichen.to_string();
+        let mut zeilen = Vec::with_capacity(self.zeilen.len() + 1);
+        if format.kopfzeile {
+            zeilen.push(self.kopf.join(&trenner));
+        }
+        for zeile in &self.zeilen {
+            zeilen.push(
+                zeile
+                    .iter()
+                    .map(|z| match z {
+                        Zelle::Zahl(w) => format.zahl(*w),
+                        Zelle::Text(t) => t.clone(),
+                    })
+                    .collect::<Vec<String>>()
+                    .join(&trenner),
+            );
+        }
+        zeilen.join("\n")
+    }
+}
+
+/**
+Ausgabeverzeichnis aller Studien. Jede Datei wird unter ihrem festen Namen im Verzeichnis
+abgelegt, Tabellen im gewählten `Format`. Mit `json` bzw. `json_zeilen` schreiben Simulationen
+zusätzlich JSON bzw. JSON-Lines.
+*/
+#[derive(Clone, Debug)]
+pub struct Ausgabe {
+    verzeichnis: PathBuf,
+    pub format: Format,
+    pub json: bool,
+    pub json_zeilen: bool,
+}
+
+impl Ausgabe {
+    pub fn neu(verzeichnis: &Path, format: Format) -> Self {
+        if format.dezimalkomma && format.trennzeichen == ',' {
+            panic!("Decimal comma needs a separator other than ','")
+        }
+        if let Err(why) = std::fs::create_dir_all(verzeichnis) {
+            panic!("Couldn't create {}: {}", verzeichnis.display(), why)
+        }
+        Ausgabe {
+            verzeichnis: verzeichnis.to_path_buf(),
+            format,
+            json: false,
+            json_zeilen: false,
+        }
+    }
+
+    pub fn datei(&self, name: &str) -> PathBuf {
+        self.verzeichnis.join(name)
+    }
+
+    pub fn schreibe_tabelle(&self, name: &str, tabelle: &Tabelle) {
+        self.schreibe_text(name, &tabelle.text(&self.format));
+    }
+
+    pub fn schreibe_json<T: Serialize>(&self, name: &str, wert: &T) {
+        match serde_json::to_string_pretty(wert) {
+            Ok(text) => self.schreibe_text(name, &text),
+            Err(why) => error!("Couldn't serialize {}: {}", name, why),
+        }
+    }
+
+    // Datei für JSON-Lines, die während der Rechnung Zeile für Zeile geschrieben wird
+    pub fn json_zeilen(&self, name: &str) -> JsonZeilen {
+        let path = self.datei(name);
+        let schreiber = match File::create(&path) {
+            Ok(file) => Some(BufWriter::new(file)),
+            Err(why) => {
+                error!("Couldn't create {}: {}", path.display(), why);
+                None
+            }
+        };
+        JsonZeilen { path, schreiber }
+    }
+
+    pub fn schreibe_text(&self, name: &str, text: &str) {
+        let path = self.datei(name);
+        let mut file = match File::create(&path) {
+            Err(why) => {
+                error!("Couldn't create {}: {}", path.display(), why);
+                return;
+            }
+            Ok(file) => file,
+        };
+        match file.write_all(text.as_bytes()) {
+            Err(why) => error!("couldn't write to {}: {}", path.display(), why),
+            Ok(_) => info!("successfully wrote to {}", path.display()),
+        }
+    }
+}
+
+/**
+Fortlaufend geschriebene JSON-Lines-Datei, ein Objekt je Zeile. Jede Zeile wird sofort
+weitergegeben, sodass die Datei schon während einer langen Rechnung gelesen werden kann.
+Nach dem ersten Fehler wird nicht weiter geschrieben.
+*/
+pub struct JsonZeilen {
+    path: PathBuf,
+    schreiber: Option<BufWriter<File>>,
+}
+
+impl JsonZeilen {
+    pub fn schreibe<T: Serialize>(&mut self, wert: &T) {
+        let Some(schreiber) = &mut self.schreiber else {
+            return;
+        };
+        let ergebnis = serde_json::to_writer(&mut *schreiber, wert)
+            .map_err(|why| why.to_string())
+            .and_then(|_| {
+                schreiber
+                    .write_all(b"\n")
+                    .and_then(|_| schreiber.flush())
+                    .map_err(|why| why.to_string())
+            });
+        if let Err(why) = ergebnis {
+            error!("couldn't write to {}: {}", self.path.display(), why);
+            self.schreiber = None;
+        }
+    }
+
+    pub fn beende(self) {
+        if self.schreiber.is_some() {
+            info!("successfully wrote to {}", self.path.display());
+        }
+    }
+}
+
+/**
+Zahlentabelle aus einer CSV-Datei mit den Namen der Kopfzeile (leer ohne Kopfzeile)
+und der Zeilennummer der Datei (ab 1) zu jeder Zeile.
+*/
+pub struct Zahlentabelle {
+    pub kopf: Vec<String>,
+    pub zeilen: Vec<Vec<f64>>,
+    pub zeilennummern: Vec<usize>,
+}
+
+/**
+Liest eine Zahlentabelle aus einer CSV-Datei, etwa die Ergebnisse einer Studie.
+Getrennt wird mit Komma, Semikolon oder Tabulator; bei Semikolon oder Tabulator darf das
+Dezimalzeichen ein Komma sein. Leere Zeilen und Zeilen mit `#` werden übersprungen,
+eine erste Zeile, die keine Zahlen enthält, gilt als Kopfzeile.
+*/
+pub fn lies_tabelle(path: &Path) -> Vec<Vec<f64>> {
+    match lies_zahlentabelle(path) {
+        Ok(tabelle) => tabelle.zeilen,
+        Err(why) => panic!("{}", why),
+    }
+}
+
+// Wie `lies_tabelle`, aber mit Kopfzeile und Zeilennummern und ohne Abbruch bei Fehlern
+pub fn lies_zahlentabelle(path: &Path) -> Result<Zahlentabelle, String> {
+    let mut file =
+        File::open(path).map_err(|why| format!("Couldn't open {}: {}", path.display(), why))?;
+    let mut s = String::new();
+    if let Err(why) = file.read_to_string(&mut s) {
+        return Err(format!("couldn't read {}: {}", path.display(), why));
+    };
+
+    let mut tabelle = Zahlentabelle {
+        kopf: Vec::new(),
+        zeilen: Vec::new(),
+        zeilennummern: Vec::new(),
+    };
+    for (nummer, zeile) in s.lines().enumerate() {
+        let zeile = zeile.trim();
+        if zeile.is_empty() || zeile.starts_with('#') {
+            continue;
+        }
+        let semikolon = zeile.contains([';', '\t']);
+        let felder: Vec<&str> = if semikolon {
+            zeile.split([';', '\t']).map(str::trim).collect()
+        } else {
+            zeile.split(',').map(str::trim).collect()
+        };
+        let werte: Result<Vec<f64>, _> = felder
+            .iter()
+            .map(|w| {
+                if semikolon {
+                    w.replace(',', ".").parse::<f64>()
+                } else {
+                    w.parse::<f64>()
+                }
+            })
+            .collect();
+        match werte {
+            Ok(werte) => {
+                tabelle.zeilen.push(werte);
+                tabelle.zeilennummern.push(nummer + 1);
+            }
+            // Kopfzeile
+            Err(_) if tabelle.zeilen.is_empty() => {
+                if tabelle.kopf.is_empty() {
+                    tabelle.kopf = felder.iter().map(|f| f.to_string()).collect();
+                }
+            }
+            Err(_) => {
+                return Err(format!(
+                    "couldn't parse line {} of {}: {}",
+                    nummer + 1,
+                    path.display(),
+                    zeile
+                ))
+            }
+        }
+    }
+    Ok(tabelle)
+}
@@ -0,0 +1,114 @@
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+static STILL: AtomicBool = AtomicBool::new(false);
+
+// Schaltet die Fortschrittsanzeige aller Studien ab, etwa für Skripte
+pub fn setze_still(still: bool) {
+    STILL.store(still, Ordering::Relaxed);
+}
+
+/**
+Fortschrittsanzeige einer Studie auf stderr mit Anzahl, Rate und geschätzter Restzeit.
+Im Terminal wird eine Zeile alle halbe Sekunde überschrieben, sonst alle zehn Sekunden
+eine neue Zeile geschrieben. `schritt` darf aus mehreren Threads aufgerufen werden.
+*/
+pub struct Fortschritt {
+    name: String,
+    gesamt: usize,
+    anfang: usize,
+    fertig: AtomicUsize,
+    start: Instant,
+    letzte_anzeige: Mutex<Instant>,
+    terminal: bool,
+}
+
+impl Fortschritt {
+    /**
+    `erledigt` sind Schritte, die schon vorher fertig waren, etwa aus einer `Sicherung`.
+    Sie zählen zum Fortschritt, aber nicht zur Rate.
+    */
+    pub fn neu(name: &str, gesamt: usize, erledigt: usize) -> Self {
+        Fortschritt {
+            name: name.to_string(),
+            gesamt,
+            anfang: erledigt,
+            fertig: AtomicUsize::new(erledigt),
+            start: Instant::now(),
+            letzte_anzeige: Mutex::new(Instant::now()),
+            terminal: std::io::stderr().is_terminal(),
+        }
+    }
+
+    pub fn schritt(&self) {
+        let fertig = self.fertig.fetch_add(1, Ordering::Relaxed) + 1;
+        if STILL.load(Ordering::Relaxed) {
+            return;
+        }
+        let abstand = if self.terminal {
+            Duration::from_millis(500)
+        } else {
+            Duration::from_secs(10)
+        };
+        // Wer die Anzeige gerade nicht bekommt, rechnet einfach weiter
+        if let Ok(mut letzte) = self.letzte_anzeige.try_lock() {
+            if letzte.elapsed() >= abstand {
+                *letzte = Instant::now();
+                self.zeige(fertig);
+            }
+        }
+    }
+
+    // Schließt die Anzeige mit der benötigten Zeit ab
+    pub fn beende(&self) {
+        if STILL.load(Ordering::Relaxed) {
+            return;
+        }
+        let fertig = self.fertig.load(Ordering::Relaxed);
+        let text = format!(
+            "{}: {}/{} in {}",
+            self.name,
+            fertig,
+            self.gesamt,
+            dauer(self.start.elapsed().as_secs_f64())
+        );
+        if self.terminal {
+            eprintln!("\r{:<70}", text);
+        } else {
+            eprintln!("{}", text);
+        }
+    }
+
+    fn zeige(&self, fertig: usize) {
+        let rate = (fertig - self.anfang) as f64 / self.start.elapsed().as_secs_f64();
+        let rest = self.gesamt.saturating_sub(fertig) as f64 / rate;
+        let text = format!(
+            "{}: {}/{} ({:.0} %), {:.1}/s, noch {}",
+            self.name,
+            fertig,
+            self.gesamt,
+            100.0 * fertig as f64 / self.gesamt.max(1) as f64,
+            rate,
+            dauer(rest)
+        );
+        if self.terminal {
+            eprint!("\r{:<70}", text);
+        } else {
+            eprintln!("{}", text);
+        }
+    }
+}
+
+fn dauer(sekunden: f64) -> String {
+    if !sekunden.is_finite() {
+        return "?".to_string();
+    }
+    let s = sekunden.round() as u64;
+    match s {
+        0..=59 => format!("{} s", s),
+        60..=3599 => format!("{} min {:02} s", s / 60, s % 60),
+        _ => format!("{} h {:02} min", s / 3600, s % 3600 / 60),
+    }
+}
@@ -1,8 +1,16 @@
-use log::{debug, error, info, trace, warn};
+use log::{debug, trace};
+use serde::Serialize;
+
+use crate::zeitintegration::Zeitintegration;
 
 const G: f64 = 9.81;
 
-pub trait Fuellquerschnitt {
+/**
+Geometrie und Verlustbeiwerte eines einzelnen Füll- oder Entleerungsquerschnitts.
+Alle Zeiten sind ab dem Öffnungsbeginn des Querschnitts angegeben.
+Querschnitte müssen zwischen Threads geteilt werden können, damit Studien parallel rechnen.
+*/
+pub trait Fuellquerschnitt: Send + Sync {
     // Fläche des geöffneten Querschnitts zu einem Zeitpunkt s
     fn querschnitt(&self, zeit: f64) -> f64;
 
@@ -26,24 +34,23 @@ pub trait Fuellquerschnitt {
     */
     fn durchflussverslust_ueberfall(
         &self,
-        schleuse: &Schleuse,
+        abstrom: &Abstrom,
         pot_hoehe: f64,
         unterehoehe: f64,
         zeit: f64,
     ) -> f64;
     fn durchflussverslust_unterstroemung(
         &self,
-        schleuse: &Schleuse,
+        abstrom: &Abstrom,
         pot_hoehe: f64,
         unterehoehe: f64,
         zeit: f64,
     ) -> f64;
-    fn durchflussverslust_schuetz(
-        &self,
-        pot_hoehe: f64,
-        unterehoehe: f64,
-        zeit: f64
-    ) -> f64;
+    /**
+    Abflussbeiwert für die Unterströmung eines Schützes, bezogen auf `quadratur_durchfluss_schuetz`.
+    Beide Höhen sind ab der Unterkante des Querschnitts angegeben.
+    */
+    fn durchflussverslust_schuetz(&self, pot_hoehe: f64, unterehoehe: f64, zeit: f64) -> f64;
 
     // Quadratur zur Ermittlung des Durchflusses
     // Die Potentialhoehe ist anzugeben auf die untere Kante des Füllquerschnitts
@@ -69,7 +76,7 @@ pub trait Fuellquerschnitt {
             rest += self.freigegebene_breite(schritt * i as f64)
                 * (2.0 * G * (pot_hoehe - (uberstroemte_hoehe))).sqrt();
         }
-        return (rest + unteregrenze + oberegrenze) * schritt;
+        (rest + unteregrenze + oberegrenze) * schritt
     }
 
     fn quadratur_durchfluss_ueberfall(
@@ -95,20 +102,35 @@ pub trait Fuellquerschnitt {
             rest += self.freigegebene_breite(uberstroemte_hoehe + schritt * i as f64)
                 * (2.0 * G * (pot_hoehe - (uberstroemte_hoehe + schritt * i as f64))).sqrt();
         }
-        return (rest + unteregrenze + oberegrenze) * schritt;
+        (rest + unteregrenze + oberegrenze) * schritt
     }
 
-    fn quadratur_durchfluss_schuetz(
-        &self,
-        pot_hoehe: f64,
-        untere_hoehe: f64,
-        zeit: f64
-    ) -> f64 {
+    // Durchfluss unter dem Schütz ohne Verluste: freigegebene Fläche mal sqrt(2 g h)
+    // Ein Rückstau durch das Unterwasser wird allein über den Abflussbeiwert erfasst
+    fn quadratur_durchfluss_schuetz(&self, pot_hoehe: f64, _untere_hoehe: f64, zeit: f64) -> f64 {
         let frei = self.freigegebene_hoehe(zeit);
-        return 0.0;
+        let n: u32 = 100;
+        let schritt = frei / (n as f64);
+        // Trapezformel!
+        let mut flaeche = (self.freigegebene_breite(0.0) + self.freigegebene_breite(frei)) / 2.0;
+        for i in 1..n {
+            flaeche += self.freigegebene_breite(schritt * i as f64);
+        }
+        flaeche * schritt * (2.0 * G * pot_hoehe).sqrt()
     }
 }
 
+/**
+Wasserkörper, in den die Querschnitte ausströmen: beim Füllen die Kammer, beim Entleeren das Unterwasser.
+Die Wassertiefe ist ab seiner Sohle angegeben, mit der Breite bestimmt sie den Austrittsverlust.
+*/
+#[derive(Clone, Copy, Debug)]
+pub struct Abstrom {
+    pub breite: f64,
+    pub wassertiefe: f64,
+    pub entleerung: bool,
+}
+
 pub struct Schleusenkammer {
     pub breite: f64,
     pub laenge: f64,
@@ -120,9 +142,72 @@ pub struct Oberhaupt {
     pub oberwassersohle: f64,
 }
 
+/**
+Abflussbeiwert für die Unterströmung eines Schützes mit der Öffnungshöhe `oeffnung`.
+Oberwasser und Unterwasser sind ab der Schützschwelle angegeben, `kontraktion` ist die Einschnürungszahl.
+Der freie Ausfluss folgt aus der Energiegleichung bis zum eingeschnürten Strahl.
+Liegt das Unterwasser höher als die konjugierte Tiefe des Strahls, ist der Ausfluss rückgestaut
+und die Wassertiefe über dem Strahl wird aus der Impulsbilanz bis zum Unterwasser bestimmt.
+Der Beiwert ist bezogen auf oeffnung * sqrt(2 g oberwasser).
+*/
+pub fn schuetz_abflussbeiwert(
+    oeffnung: f64,
+    oberwasser: f64,
+    unterwasser: f64,
+    kontraktion: f64,
+) -> f64 {
+    if oeffnung <= 0.0 || oberwasser <= 0.0 || unterwasser >= oberwasser {
+        return 0.0;
+    }
+    // Tiefe des eingeschnürten Strahls
+    let strahl = (kontraktion * oeffnung).min(oberwasser);
+    // Spezifischer Durchfluss bei einer Wassertiefe über dem Strahl
+    let durchfluss = |tiefe: f64| {
+        (2.0 * G * (oberwasser - tiefe) / (1.0 / strahl.powi(2) - 1.0 / oberwasser.powi(2))).sqrt()
+    };
+
+    let frei = durchfluss(strahl);
+    let froude = frei.powi(2) / (G * strahl.powi(3));
+    let konjugiert = strahl / 2.0 * ((1.0 + 8.0 * froude).sqrt() - 1.0);
+
+    let q = if unterwasser <= konjugiert {
+        frei
+    } else {
+        // Impulsbilanz zwischen eingeschnürtem Strahl und Unterwasser, Bisektion über die Tiefe
+        let impuls = |tiefe: f64| {
+            let q = durchfluss(tiefe);
+            tiefe.powi(2) / 2.0 + q.powi(2) / (G * strahl)
+                - unterwasser.powi(2) / 2.0
+                - q.powi(2) / (G * unterwasser)
+        };
+        let mut unten = strahl;
+        let mut oben = unterwasser;
+        for _ in 0..60 {
+            let mitte = (unten + oben) / 2.0;
+            if impuls(mitte) < 0.0 {
+                unten = mitte;
+            } else {
+                oben = mitte;
+            }
+        }
+        durchfluss((unten + oben) / 2.0)
+    };
+    trace!(
+        "Schütz: a = {:?}, h_o = {:?}, h_u = {:?}, h_konj = {:?}, q = {:?}",
+        oeffnung,
+        oberwasser,
+        unterwasser,
+        konjugiert,
+        q
+    );
+    q / (oeffnung * (2.0 * G * oberwasser).sqrt())
+}
+
+// Querschnitt mit Höhenlage und Startzeit seiner Öffnung
 pub struct Fuellquerschnittssystem {
     pub hoehe: f64,     // Unterkante des Querschnitts ab Bezugshöhe
     pub startzeit: f64, // In Sekunden
+    pub schuetz: bool,  // Ob der Querschnitt über ein Schütz gesteuert wird
     pub fuellquerschnitt: Box<dyn Fuellquerschnitt>,
 }
 
@@ -134,10 +219,12 @@ impl Fuellquerschnittssystem {
     Die untere hoehe gibt die Höhe des Kammerwassers bzw des UWs an. (= Ob Querschnitt teilweise rückgestaut ist.)
     Beide Höhen sind ausgehend von der Bezugshöhe angegeben.
     Weitere Konstruktive Maße sind der Schleuse zu entnehmen.
+    Schützgesteuerte Querschnitte werden unterströmt, solange das Schütz noch eintaucht und den
+    Querschnitt nicht vollständig freigegeben hat, danach gilt der Abfluss durch den Querschnitt.
     */
     pub fn durchfluss(
         &self,
-        schleuse: &Schleuse,
+        abstrom: &Abstrom,
         oberehoehe: f64,
         unterehoehe: f64,
         zeit: f64,
@@ -145,36 +232,107 @@ impl Fuellquerschnittssystem {
         if zeit <= self.startzeit {
             return 0.0;
         }
+        let pot_hoehe = oberehoehe - self.hoehe;
+        let ueberstroemhoehe = (unterehoehe - self.hoehe).max(0.0);
+        let zeit = zeit - self.startzeit;
+
+        if self.schuetz
+            && !self.fuellquerschnitt.is_fully_opened(zeit)
+            && self.fuellquerschnitt.freigegebene_hoehe(zeit) < pot_hoehe
+        {
+            let mu = self.verlust_schuetz(abstrom, oberehoehe, unterehoehe, zeit);
+            trace!("mu_schuetz: {:?}", mu);
+            return mu
+                * self.fuellquerschnitt.quadratur_durchfluss_schuetz(
+                    pot_hoehe,
+                    ueberstroemhoehe,
+                    zeit,
+                );
+        }
+        self.durchfluss_querschnitt(abstrom, oberehoehe, unterehoehe, zeit)
+    }
+
+    /**
+    Abflussbeiwert des teilweise geöffneten Schützes einschließlich der Verluste des Querschnitts.
+    Die Verluste ergeben sich aus dem Abfluss durch den vollständig geöffneten Querschnitt bei
+    denselben Wasserständen, abzüglich des Anteils des Schützes bei voller Öffnung. Sie wachsen mit
+    dem Quadrat des geöffneten Anteils, sodass der Abfluss beim Umschalten auf den Querschnitt stetig ist.
+    */
+    fn verlust_schuetz(
+        &self,
+        abstrom: &Abstrom,
+        oberehoehe: f64,
+        unterehoehe: f64,
+        zeit: f64,
+    ) -> f64 {
+        let pot_hoehe = oberehoehe - self.hoehe;
+        let ueberstroemhoehe = (unterehoehe - self.hoehe).max(0.0);
+        let querschnitt = &self.fuellquerschnitt;
+        let schuetz = querschnitt.durchflussverslust_schuetz(pot_hoehe, ueberstroemhoehe, zeit);
+        if schuetz <= 0.0 {
+            return 0.0;
+        }
+        // Jeder Querschnitt ist nach unbegrenzter Zeit vollständig geöffnet
+        let voll = f64::INFINITY;
+        let schuetz_voll =
+            querschnitt.durchflussverslust_schuetz(pot_hoehe, ueberstroemhoehe, voll);
+        let bezug_voll =
+            querschnitt.quadratur_durchfluss_schuetz(pot_hoehe, ueberstroemhoehe, voll);
+        let querschnitt_voll =
+            self.durchfluss_querschnitt(abstrom, oberehoehe, unterehoehe, voll) / bezug_voll;
+        if schuetz_voll <= 0.0 || querschnitt_voll <= 0.0 {
+            return schuetz;
+        }
+        let zeta = (1.0 / querschnitt_voll.powi(2) - 1.0 / schuetz_voll.powi(2)).max(0.0);
+        let n = querschnitt.querschnitt_prozent_zeit(zeit).clamp(0.0, 1.0);
+        1.0 / (1.0 / schuetz.powi(2) + n.powi(2) * zeta).sqrt()
+    }
+
+    // Durchfluss durch den Querschnitt ohne Schütz, `zeit` ab Öffnungsbeginn
+    fn durchfluss_querschnitt(
+        &self,
+        abstrom: &Abstrom,
+        oberehoehe: f64,
+        unterehoehe: f64,
+        zeit: f64,
+    ) -> f64 {
         let pot_hoehe = oberehoehe - self.hoehe;
         let ueberstroemhoehe = (unterehoehe - self.hoehe).max(0.0);
         // Block für die Verluste
         let mu_a = self.fuellquerschnitt.durchflussverslust_ueberfall(
-            schleuse,
+            abstrom,
             pot_hoehe,
             unterehoehe,
-            zeit - self.startzeit,
+            zeit,
         );
         let mu_s = self.fuellquerschnitt.durchflussverslust_unterstroemung(
-            schleuse,
+            abstrom,
             pot_hoehe,
             unterehoehe,
-            zeit - self.startzeit,
+            zeit,
         );
 
         if unterehoehe < self.hoehe {
             trace!("mu_a, mu_s, mu_as: {:?},{:?},{:?}", mu_a, mu_s, 0.0);
-            mu_a * self.fuellquerschnitt.quadratur_durchfluss_ueberfall(
+            mu_a * self
+                .fuellquerschnitt
+                .quadratur_durchfluss_ueberfall(pot_hoehe, 0.0, zeit)
+        } else if abstrom.entleerung
+            && self.hoehe + self.fuellquerschnitt.freigegebene_hoehe(zeit) < unterehoehe
+        {
+            // Entleerungsquerschnitt vollständig unter dem UW, ein Ausfluss ins Freie entfällt
+            trace!("mu_a, mu_s, mu_as: {:?},{:?},{:?}", mu_a, mu_s, mu_s);
+            mu_s * self.fuellquerschnitt.quadratur_durchfluss_unterstroemung(
                 pot_hoehe,
-                0.0,
-                zeit - self.startzeit,
+                ueberstroemhoehe,
+                zeit,
             )
         } else {
             let fuellhoehe = self
                 .fuellquerschnitt
-                .freigegebene_hoehe(zeit - self.startzeit)
+                .freigegebene_hoehe(zeit)
                 .min(ueberstroemhoehe);
-            let mu_as = mu_a
-                * (1.0 - self.fuellquerschnitt.querschnitt_prozent_hoehe(fuellhoehe))
+            let mu_as = mu_a * (1.0 - self.fuellquerschnitt.querschnitt_prozent_hoehe(fuellhoehe))
                 + (mu_s * self.fuellquerschnitt.querschnitt_prozent_hoehe(fuellhoehe));
 
             trace!("mu_a, mu_s, mu_as: {:?},{:?},{:?}", mu_a, mu_s, mu_as);
@@ -182,89 +340,220 @@ impl Fuellquerschnittssystem {
                 * (self.fuellquerschnitt.quadratur_durchfluss_unterstroemung(
                     pot_hoehe,
                     ueberstroemhoehe,
-                    zeit - self.startzeit,
+                    zeit,
                 ) + self.fuellquerschnitt.quadratur_durchfluss_ueberfall(
                     pot_hoehe,
                     ueberstroemhoehe,
-                    zeit - self.startzeit,
+                    zeit,
                 ))
         }
     }
 
-    fn ist_ueberstroemt(&self, unterehoehe: f64, zeit: f64) -> bool {
-        return self.hoehe < unterehoehe;
+    fn ist_ueberstroemt(&self, unterehoehe: f64, _zeit: f64) -> bool {
+        self.hoehe < unterehoehe
     }
 
     fn ist_vollstandig_ueberstroemt(&self, unterehoehe: f64, zeit: f64) -> bool {
-        return self.hoehe
+        self.hoehe
             + self
                 .fuellquerschnitt
                 .freigegebene_hoehe(zeit - self.startzeit)
-            < unterehoehe;
+            < unterehoehe
     }
 
     fn ist_geoffnet(&self, zeit: f64) -> bool {
-        return zeit > self.startzeit;
+        zeit > self.startzeit
     }
 
     fn ist_vollstandig_geoffnet(&self, zeit: f64) -> bool {
-        return self.fuellquerschnitt.is_fully_opened(zeit - self.startzeit);
+        self.fuellquerschnitt.is_fully_opened(zeit - self.startzeit)
+    }
+
+    fn ueberstroemungsstatus(&self, unterehoehe: f64, zeit: f64) -> FuellsystemStatus {
+        if self.ist_vollstandig_ueberstroemt(unterehoehe, zeit) {
+            FuellsystemStatus::VollUeberfuellt
+        } else if self.ist_ueberstroemt(unterehoehe, zeit) {
+            FuellsystemStatus::StartUeberfuellung
+        } else {
+            FuellsystemStatus::Unbekannt
+        }
+    }
+
+    fn oeffnungsstatus(&self, zeit: f64) -> FuellsystemStatus {
+        if self.ist_vollstandig_geoffnet(zeit) {
+            FuellsystemStatus::VollGeoeffnet
+        } else if self.ist_geoffnet(zeit) {
+            FuellsystemStatus::StartOeffnung
+        } else {
+            FuellsystemStatus::Unbekannt
+        }
+    }
+}
+
+// Genauigkeit der Ereigniszeiten in s
+const EREIGNISGENAUIGKEIT: f64 = 1e-9;
+
+/**
+Sucht per Bisektion den Zeitpunkt innerhalb eines Zeitschritts, ab dem `ist_eingetreten` gilt.
+Der Schritt wird durch Zeit und untere Höhe an seinem Anfang und Ende beschrieben,
+dazwischen wird die untere Höhe linear interpoliert. Liegt der Zeitpunkt auf
+`EREIGNISGENAUIGKEIT` an Anfang oder Ende des Schritts, wird genau dieser zurückgegeben,
+ein Ereignis zur Startzeit erhält so genau die Startzeit.
+*/
+fn ereigniszeit(
+    von: (f64, f64),
+    bis: (f64, f64),
+    ist_eingetreten: impl Fn(f64, f64) -> bool,
+) -> f64 {
+    let hoehe_bei = |zeit: f64| {
+        if bis.0 > von.0 {
+            von.1 + (bis.1 - von.1) * (zeit - von.0) / (bis.0 - von.0)
+        } else {
+            bis.1
+        }
+    };
+    let mut unten = von.0;
+    let mut oben = bis.0;
+    for _ in 0..60 {
+        if oben - unten <= EREIGNISGENAUIGKEIT {
+            break;
+        }
+        let mitte = (unten + oben) / 2.0;
+        if ist_eingetreten(mitte, hoehe_bei(mitte)) {
+            oben = mitte;
+        } else {
+            unten = mitte;
+        }
+    }
+    if oben - von.0 <= EREIGNISGENAUIGKEIT {
+        von.0
+    } else if bis.0 - oben <= EREIGNISGENAUIGKEIT {
+        bis.0
+    } else {
+        oben
     }
 }
 
+// Alle Querschnitte eines Füll- oder Entleerungssystems
 pub struct Fuellsystem {
-    pub querschnitte: Vec<Box<Fuellquerschnittssystem>>,
+    pub querschnitte: Vec<Fuellquerschnittssystem>,
 }
 
 impl Fuellsystem {
-    fn durchfluss(&self, schleuse: &Schleuse, unterehoehe: f64, oberehoehe: f64, zeit: f64) -> f64 {
+    fn durchfluss(&self, abstrom: &Abstrom, unterehoehe: f64, oberehoehe: f64, zeit: f64) -> f64 {
         let mut res = 0.0;
         for i in &self.querschnitte {
-            res += i.durchfluss(schleuse, oberehoehe, unterehoehe, zeit);
+            res += i.durchfluss(abstrom, oberehoehe, unterehoehe, zeit);
         }
-        return res;
+        res
     }
 
     pub fn anzahl_fuellsysteme(&self) -> usize {
         self.querschnitte.len()
     }
     pub fn ist_ueberstroemt(&self, unterehoehe: f64, zeit: f64) -> Vec<FuellsystemStatus> {
-        let mut vec = Vec::new();
-        for i in &self.querschnitte {
-            if i.ist_vollstandig_ueberstroemt(unterehoehe, zeit) {
-                vec.push(FuellsystemStatus::VollUeberfuellt)
-            } else if i.ist_ueberstroemt(unterehoehe, zeit) {
-                vec.push(FuellsystemStatus::StartUeberfuellung)
-            } else {
-                vec.push(FuellsystemStatus::Unbekannt)
-            }
-        }
-        return vec;
+        self.querschnitte
+            .iter()
+            .map(|i| i.ueberstroemungsstatus(unterehoehe, zeit))
+            .collect()
     }
     pub fn oeffnungsstatus(&self, zeit: f64) -> Vec<FuellsystemStatus> {
-        let mut vec = Vec::new();
-        for i in &self.querschnitte {
-            if i.ist_vollstandig_geoffnet(zeit) {
-                vec.push(FuellsystemStatus::VollGeoeffnet)
-            } else if i.ist_geoffnet(zeit) {
-                vec.push(FuellsystemStatus::StartOeffnung)
-            } else {
-                vec.push(FuellsystemStatus::Unbekannt)
+        self.querschnitte
+            .iter()
+            .map(|i| i.oeffnungsstatus(zeit))
+            .collect()
+    }
+
+    /**
+    Vergleicht den Öffnungs- und Überströmungsstatus der Querschnitte am Ende eines Zeitschritts
+    mit dem zuletzt bekannten Status und gibt die eingetretenen Events zurück.
+    `von` und `bis` sind Zeit und untere Höhe zu Beginn und Ende des Schritts,
+    der genaue Zeitpunkt eines Events wird innerhalb des Schritts per Bisektion bestimmt.
+    Der übergebene Status wird dabei fortgeschrieben.
+    */
+    pub fn pruefe_ereignisse(
+        &self,
+        statusoffen: &mut [FuellsystemStatus],
+        statusueberfuellt: &mut [FuellsystemStatus],
+        von: (f64, f64),
+        bis: (f64, f64),
+    ) -> Vec<Event> {
+        let mut events = Vec::new();
+        let momentanstroem = self.ist_ueberstroemt(bis.1, bis.0);
+        let momentanoeff = self.oeffnungsstatus(bis.0);
+
+        for i in 0..self.anzahl_fuellsysteme() {
+            let querschnitt = &self.querschnitte[i];
+            if statusoffen[i] != momentanoeff[i] {
+                let zeit = ereigniszeit(von, bis, |zeit, _| {
+                    querschnitt.oeffnungsstatus(zeit) == momentanoeff[i]
+                });
+                match &momentanoeff[i] {
+                    FuellsystemStatus::StartOeffnung => {
+                        events.push(Event {
+                            desc: String::from("SG"),
+                            zeit,
+                            status: FuellsystemStatus::StartOeffnung,
+                        });
+                        statusoffen[i] = FuellsystemStatus::StartOeffnung
+                    }
+                    FuellsystemStatus::VollGeoeffnet => {
+                        events.push(Event {
+                            desc: String::from("VG"),
+                            zeit,
+                            status: FuellsystemStatus::VollGeoeffnet,
+                        });
+                        statusoffen[i] = FuellsystemStatus::VollGeoeffnet
+                    }
+                    _ => {}
+                };
+            }
+            if statusueberfuellt[i] != momentanstroem[i] {
+                let zeit = ereigniszeit(von, bis, |zeit, unterehoehe| {
+                    querschnitt.ueberstroemungsstatus(unterehoehe, zeit) == momentanstroem[i]
+                });
+                match &momentanstroem[i] {
+                    FuellsystemStatus::StartUeberfuellung => {
+                        events.push(Event {
+                            desc: String::from("SU"),
+                            zeit,
+                            status: FuellsystemStatus::StartUeberfuellung,
+                        });
+                        statusueberfuellt[i] = FuellsystemStatus::StartUeberfuellung;
+                    }
+                    FuellsystemStatus::VollUeberfuellt => {
+                        events.push(Event {
+                            desc: String::from("VU"),
+                            zeit,
+                            status: FuellsystemStatus::VollUeberfuellt,
+                        });
+                        statusueberfuellt[i] = FuellsystemStatus::VollUeberfuellt;
+                    }
+                    _ => {}
+                }
             }
         }
-        return vec;
+        events
     }
 }
+// Unterhaupt mit Unterwasserstand und Entleerungssystem
 pub struct Unterhaupt {
     pub unterwasser: f64,
     pub unterwasserbreite: f64,
     pub unterwassersohle: f64,
+    // Entleerungssystem, Höhen der Querschnitte ab Unterwassersohle
+    pub entleersystem: Fuellsystem,
 }
+/**
+Schleuse aus Kammer, Ober- und Unterhaupt. Das Füllsystem liegt im Oberhaupt,
+seine Höhen sind ab der Oberwassersohle angegeben.
+*/
 pub struct Schleuse {
     pub kammer: Schleusenkammer,
     pub oberhaupt: Oberhaupt,
     pub unterhaupt: Unterhaupt,
     pub fuellsystem: Fuellsystem,
+    pub zeitintegration: Zeitintegration,
 }
 
 impl Oberhaupt {
@@ -285,22 +574,32 @@ impl Schleusenkammer {
     }
 }
 
-#[derive(Debug)]
+// Änderung des Öffnungs- oder Überströmungszustands eines Querschnitts
+#[derive(Debug, Serialize)]
 pub struct Event {
     pub desc: String,
+    pub zeit: f64, // Genauer Zeitpunkt des Events in Sekunden
     pub status: FuellsystemStatus,
 }
 
+/**
+Ergebnis eines Zeitschritts der Simulation. Der Kammerwasserspiegel ist ab der Unterwassersohle,
+der Durchfluss in m³/s und die Durchflusszunahme in m³/s² angegeben.
+Beim Serialisieren fehlen die Ereignisse, sie werden getrennt ausgegeben.
+*/
+#[derive(Serialize)]
 pub struct Simulationsschritt {
     pub iteration: u32,
+    #[serde(rename = "zeit")]
     pub zeitschritt: f64,
     pub kammerwasserspiegel: f64,
     pub durchfluss: f64,
     pub durchflusszunahme: f64,
+    #[serde(skip)]
     pub events: Vec<Event>,
 }
 
-#[derive(Clone, PartialEq, Debug)]
+#[derive(Clone, PartialEq, Debug, Serialize)]
 pub enum FuellsystemStatus {
     Unbekannt,
     StartOeffnung,
@@ -318,120 +617,283 @@ impl Schleuse {
         self.kammer.grundflaeche() * (self.hubhoehe() + self.unterhaupt.wasserspiegel())
     }
 
+    /**
+    Simuliert das Füllen der Kammer vom Unterwasser bis zum Oberwasser über das Füllsystem.
+    */
     pub fn fuell_schleuse(&self) -> Vec<Simulationsschritt> {
-        let mut kammerspiegel = self.unterhaupt.wasserspiegel();
-        let zeitschritt = 1.0;
-        let mut i = 1;
-        let mut volume = self.kammer.grundflaeche() * kammerspiegel;
-        let max_iterations = 20000;
+        self.fuell_schleuse_mit(|_| {})
+    }
 
-        let mut result_vec = Vec::new();
-        let mut durchfluss = 0.0;
+    /**
+    Wie `fuell_schleuse`, `je_schritt` erhält jeden Zeitschritt, sobald er berechnet ist,
+    etwa um lange Simulationen fortlaufend zu schreiben.
+    */
+    pub fn fuell_schleuse_mit(
+        &self,
+        je_schritt: impl FnMut(&Simulationsschritt),
+    ) -> Vec<Simulationsschritt> {
+        let kammerspiegel = self.unterhaupt.wasserspiegel();
         debug!(
             "The start values for iteration in fuell_schleuse are: HKA = {:?}, volume = {:?}",
-            kammerspiegel, volume
+            kammerspiegel,
+            self.kammer.grundflaeche() * kammerspiegel
         );
+        // Bezugshöhe des Füllsystems ist die Oberwassersohle
+        let sohlenabstand = self.oberhaupt.oberwassersohle - self.unterhaupt.unterwassersohle;
+        self.simuliere(
+            &self.fuellsystem,
+            kammerspiegel,
+            self.oberhaupt.oberwasser - self.unterhaupt.unterwassersohle,
+            |kammerspiegel| {
+                (
+                    kammerspiegel - sohlenabstand,
+                    self.oberhaupt.wasserspiegel(),
+                )
+            },
+            |unterehoehe| Abstrom {
+                breite: self.kammer.breite,
+                wassertiefe: unterehoehe + sohlenabstand,
+                entleerung: false,
+            },
+            je_schritt,
+        )
+    }
+
+    /**
+    Simuliert das Entleeren der Kammer über das Entleerungssystem des Unterhaupts.
+    Bezugshöhe der Querschnitte ist die Unterwassersohle, der Durchfluss ist positiv aus der Kammer heraus.
+    */
+    pub fn entleere_schleuse(&self) -> Vec<Simulationsschritt> {
+        self.entleere_schleuse_mit(|_| {})
+    }
 
-        let anzahl_fuellsys = self.fuellsystem.querschnitte.len();
-        let mut statusueberfuellt_fuellsys = self.fuellsystem.ist_ueberstroemt(
-            (kammerspiegel - (self.oberhaupt.oberwassersohle - self.unterhaupt.unterwassersohle))
-                .max(0.0),
-            0.0,
+    // Wie `entleere_schleuse`, mit jedem Zeitschritt wie bei `fuell_schleuse_mit`
+    pub fn entleere_schleuse_mit(
+        &self,
+        je_schritt: impl FnMut(&Simulationsschritt),
+    ) -> Vec<Simulationsschritt> {
+        let kammerspiegel = self.wasservolumen() / self.kammer.grundflaeche();
+        debug!(
+            "The start values for iteration in entleere_schleuse are: HKA = {:?}, volume = {:?}",
+            kammerspiegel,
+            self.wasservolumen()
         );
-        let mut statusoffen_fuellsys = self.fuellsystem.oeffnungsstatus(0.0);
+        self.simuliere(
+            &self.unterhaupt.entleersystem,
+            kammerspiegel,
+            self.unterhaupt.wasserspiegel(),
+            |kammerspiegel| (self.unterhaupt.wasserspiegel(), kammerspiegel),
+            |unterehoehe| Abstrom {
+                breite: self.unterhaupt.unterwasserbreite,
+                wassertiefe: unterehoehe,
+                entleerung: true,
+            },
+            je_schritt,
+        )
+    }
 
-        while kammerspiegel < self.oberhaupt.oberwasser - self.unterhaupt.unterwassersohle
-            && i < max_iterations
+    /**
+    Integriert die Kammerbilanz vom Startwasserspiegel bis zum Zielwasserspiegel.
+    `hoehen` liefert zu einem Kammerwasserspiegel die untere und obere Höhe für das Füllsystem.
+    Die untere Höhe darf negativ sein, für den Durchfluss wird sie auf die Bezugshöhe begrenzt.
+    `abstrom` beschreibt zur begrenzten unteren Höhe den Wasserkörper, in den ausgeströmt wird.
+    Liegt das Ziel unter dem Start, wird entleert und der Durchfluss zählt aus der Kammer heraus.
+    */
+    fn simuliere(
+        &self,
+        system: &Fuellsystem,
+        start: f64,
+        ziel: f64,
+        hoehen: impl Fn(f64) -> (f64, f64),
+        abstrom: impl Fn(f64) -> Abstrom,
+        mut je_schritt: impl FnMut(&Simulationsschritt),
+    ) -> Vec<Simulationsschritt> {
+        let integration = &self.zeitintegration;
+        let richtung = if ziel >= start { 1.0 } else { -1.0 };
+        let durchfluss_bei = |zeit: f64, kammerspiegel: f64| {
+            let (unterehoehe, oberehoehe) = hoehen(kammerspiegel);
+            let unterehoehe = unterehoehe.max(0.0);
+            let durchfluss =
+                system.durchfluss(&abstrom(unterehoehe), unterehoehe, oberehoehe, zeit);
+            if durchfluss.is_nan() {
+                0.0
+            } else {
+                durchfluss
+            }
+        };
+        // Änderung des Kammerwasserspiegels
+        let spiegelaenderung = |zeit: f64, kammerspiegel: f64| {
+            richtung * durchfluss_bei(zeit, kammerspiegel) / self.kammer.grundflaeche()
+        };
+
+        let mut kammerspiegel = start;
+        let mut zeit = 0.0;
+        let mut schrittweite = integration.zeitschritt;
+        let mut i = 1;
+        let mut durchfluss = 0.0;
+        let mut result_vec = Vec::new();
+
+        let mut statusueberfuellt = system.ist_ueberstroemt(hoehen(kammerspiegel).0, 0.0);
+        let mut statusoffen = system.oeffnungsstatus(0.0);
+
+        while richtung * (ziel - kammerspiegel) > integration.endabstand()
+            && i < integration.max_iterationen
         {
-            kammerspiegel = volume / self.kammer.grundflaeche();
-            let unterehoehe = (kammerspiegel
-                - (self.oberhaupt.oberwassersohle - self.unterhaupt.unterwassersohle))
-                .max(0.0);
-            let oberehoehe = self.oberhaupt.wasserspiegel();
+            let von = (zeit, hoehen(kammerspiegel).0);
+            let (h, neu, naechste) =
+                integration.schritt(spiegelaenderung, zeit, kammerspiegel, schrittweite);
+            zeit += h;
+            kammerspiegel = neu;
+            schrittweite = naechste;
+
             let durchfluss_alt = durchfluss;
-            durchfluss = self.fuellsystem.durchfluss(
-                &self,
-                unterehoehe,
-                oberehoehe,
-                zeitschritt * (i as f64),
-            );
-            let durchfluss = if durchfluss.is_nan() { 0.0 } else { durchfluss };
-            volume += durchfluss * zeitschritt;
+            durchfluss = durchfluss_bei(zeit, kammerspiegel);
 
             //Sind irgendwelche Events eingetreten?
-            let mut events = Vec::new();
-
-            {
-                // Droppen ist wichtig
-                let momentanstroem = self
-                    .fuellsystem
-                    .ist_ueberstroemt(unterehoehe, zeitschritt * (i as f64));
-
-                let momentanoeff = self.fuellsystem.oeffnungsstatus(zeitschritt * (i as f64));
-
-                for i in 0..anzahl_fuellsys {
-                    if statusoffen_fuellsys[i] != momentanoeff[i] {
-                        match &momentanoeff[i] {
-                            FuellsystemStatus::StartOeffnung => {
-                                events.push(Event {
-                                    desc: String::from("SG"),
-                                    status: FuellsystemStatus::StartOeffnung,
-                                });
-                                statusoffen_fuellsys[i] = FuellsystemStatus::StartOeffnung
-                            }
-                            FuellsystemStatus::VollGeoeffnet => {
-                                events.push(Event {
-                                    desc: String::from("VG"),
-                                    status: FuellsystemStatus::VollGeoeffnet,
-                                });
-                                statusoffen_fuellsys[i] = FuellsystemStatus::VollGeoeffnet
-                            }
-                            _ => {}
-                        };
-                    }
-                    if statusueberfuellt_fuellsys[i] != momentanstroem[i] {
-                        match &momentanstroem[i] {
-                            FuellsystemStatus::StartUeberfuellung => {
-                                events.push(Event {
-                                    desc: String::from("SU"),
-                                    status: FuellsystemStatus::StartUeberfuellung,
-                                });
-                                statusueberfuellt_fuellsys[i] =
-                                    FuellsystemStatus::StartUeberfuellung;
-                            }
-                            FuellsystemStatus::VollUeberfuellt => {
-                                events.push(Event {
-                                    desc: String::from("VU"),
-                                    status: FuellsystemStatus::VollUeberfuellt,
-                                });
-                                statusueberfuellt_fuellsys[i] = FuellsystemStatus::VollUeberfuellt;
-                            }
-                            _ => {}
-                        }
-                    }
-                }
-            }
-
-            //let wellengeschwindigkeit = (kammerspiegel * G).sqrt();
-            //let wasserspiegelneigung = (durchfluss - durchfluss_alt)
-            //    / (zeitschritt
-            //        * self.kammer.breite
-            //        * wellengeschwindigkeit
-            //        * wellengeschwindigkeit)
-            //    * 10.0e3;
+            let events = system.pruefe_ereignisse(
+                &mut statusoffen,
+                &mut statusueberfuellt,
+                von,
+                (zeit, hoehen(kammerspiegel).0),
+            );
 
-            result_vec.push(Simulationsschritt {
+            let schritt = Simulationsschritt {
                 iteration: i,
-                zeitschritt: zeitschritt * f64::from(i),
+                zeitschritt: zeit,
                 kammerwasserspiegel: kammerspiegel,
-                durchfluss: durchfluss,
-                durchflusszunahme: (durchfluss - durchfluss_alt) / zeitschritt,
-                events: events,
-            });
+                durchfluss,
+                durchflusszunahme: (durchfluss - durchfluss_alt) / h,
+                events,
+            };
+            je_schritt(&schritt);
+            result_vec.push(schritt);
 
             i += 1;
         }
 
-        return result_vec;
+        result_vec
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bruteforce::{FuellRechteck, Verlustbeiwerte, Vorlage};
+
+    #[test]
+    fn schuetz_freier_ausfluss() {
+        // Freier Ausfluss: mu = delta / sqrt(1 + delta a / h)
+        let (a, h, delta) = (0.5, 4.0, 0.61);
+        let mu = schuetz_abflussbeiwert(a, h, 0.0, delta);
+        assert!((mu - delta / (1.0 + delta * a / h).sqrt()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn schuetz_rueckgestauter_ausfluss() {
+        let (a, h, delta) = (0.5, 4.0, 0.61);
+        let frei = schuetz_abflussbeiwert(a, h, 0.0, delta);
+        let strahl = delta * a;
+        let froude = 2.0 * h * h / (strahl * (h + strahl));
+        let konjugiert = strahl / 2.0 * ((1.0 + 8.0 * froude).sqrt() - 1.0);
+        // Bis zur konjugierten Tiefe frei, darüber rückgestaut und mit dem Unterwasser abnehmend
+        assert_eq!(schuetz_abflussbeiwert(a, h, 0.99 * konjugiert, delta), frei);
+        let knapp = schuetz_abflussbeiwert(a, h, 1.001 * konjugiert, delta);
+        let mitte = schuetz_abflussbeiwert(a, h, 0.5 * (konjugiert + h), delta);
+        let hoch = schuetz_abflussbeiwert(a, h, 0.95 * h, delta);
+        assert!(0.0 < hoch && hoch < mitte && mitte < knapp && knapp < frei);
+        assert_eq!(schuetz_abflussbeiwert(a, h, h, delta), 0.0);
+    }
+
+    fn system(schuetz: bool, verluste: Verlustbeiwerte) -> Fuellquerschnittssystem {
+        Fuellquerschnittssystem {
+            hoehe: 0.0,
+            startzeit: 0.0,
+            schuetz,
+            fuellquerschnitt: Box::new(FuellRechteck {
+                oeffnungsgeschwindigkeit: 0.01,
+                breite: 2.0,
+                hoehe: 0.5,
+                verluste,
+            }),
+        }
+    }
+
+    #[test]
+    fn schuetz_geht_bei_voller_oeffnung_in_den_querschnitt_ueber() {
+        let abstrom = Abstrom {
+            breite: 12.0,
+            wassertiefe: 3.0,
+            entleerung: false,
+        };
+        let mit = system(true, Verlustbeiwerte::default());
+        let ohne = system(false, Verlustbeiwerte::default());
+        // Vollständig geöffnet gilt der Querschnitt
+        let q = ohne.durchfluss(&abstrom, 10.0, 3.0, 60.0);
+        assert_eq!(mit.durchfluss(&abstrom, 10.0, 3.0, 60.0), q);
+        // Kurz davor schließt der Abfluss unter dem Schütz stetig an
+        let kurz_davor = mit.durchfluss(&abstrom, 10.0, 3.0, 49.999);
+        assert!((kurz_davor - q).abs() < 1e-3 * q);
+        // Teilweise geöffnet gehen die Verluste des Querschnitts ein
+        let borda = system(true, Vorlage::Bordacarnot.beiwerte());
+        let teilweise = mit.durchfluss(&abstrom, 10.0, 3.0, 20.0);
+        assert!(teilweise < ohne.durchfluss(&abstrom, 10.0, 3.0, 20.0));
+        assert_ne!(teilweise, borda.durchfluss(&abstrom, 10.0, 3.0, 20.0));
+    }
+
+    #[test]
+    fn ereigniszeit_auf_die_genauigkeit() {
+        // Innerhalb des Schritts auf EREIGNISGENAUIGKEIT genau, ohne Fangen an den Enden
+        let zeit = ereigniszeit((3.0, 0.0), (4.0, 0.0), |zeit, _| zeit >= 3.3);
+        assert!((3.3..=3.3 + EREIGNISGENAUIGKEIT).contains(&zeit));
+        let knapp = 3.0 + 2.0 * EREIGNISGENAUIGKEIT;
+        let zeit = ereigniszeit((3.0, 0.0), (4.0, 0.0), |zeit, _| zeit >= knapp);
+        assert!(zeit > 3.0 && (knapp..=knapp + EREIGNISGENAUIGKEIT).contains(&zeit));
+        // Näher als EREIGNISGENAUIGKEIT an Anfang oder Ende gilt genau die Grenze des Schritts
+        let anfang = ereigniszeit((3.0, 0.0), (4.0, 0.0), |zeit, _| {
+            zeit >= 3.0 + 0.5 * EREIGNISGENAUIGKEIT
+        });
+        assert_eq!(anfang, 3.0);
+        let ende = ereigniszeit((3.0, 0.0), (4.0, 0.0), |zeit, _| {
+            zeit >= 4.0 - 0.5 * EREIGNISGENAUIGKEIT
+        });
+        assert_eq!(ende, 4.0);
+        // Die untere Höhe wird im Schritt linear interpoliert
+        let zeit = ereigniszeit((10.0, -0.2), (20.0, 0.3), |_, hoehe| hoehe > 0.0);
+        assert!((zeit - 14.0).abs() <= EREIGNISGENAUIGKEIT);
+    }
+
+    #[test]
+    fn ereignisse_im_zeitschritt() {
+        // Das Rechteck ist nach 50 s vollständig geöffnet
+        let system = Fuellsystem {
+            querschnitte: vec![system(false, Verlustbeiwerte::default())],
+        };
+        let mut offen = vec![FuellsystemStatus::StartOeffnung];
+        let mut ueberfuellt = vec![FuellsystemStatus::Unbekannt];
+        let events =
+            system.pruefe_ereignisse(&mut offen, &mut ueberfuellt, (45.0, -1.0), (55.0, -1.0));
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].status, FuellsystemStatus::VollGeoeffnet);
+        assert!((events[0].zeit - 50.0).abs() <= EREIGNISGENAUIGKEIT);
+        assert_eq!(offen[0], FuellsystemStatus::VollGeoeffnet);
+
+        // Bei 50 s gilt die Öffnung noch nicht als vollständig, der folgende Schritt fängt das Event am Anfang
+        let mut offen = vec![FuellsystemStatus::StartOeffnung];
+        let events =
+            system.pruefe_ereignisse(&mut offen, &mut ueberfuellt, (40.0, -1.0), (50.0, -1.0));
+        assert!(events.is_empty());
+        let events =
+            system.pruefe_ereignisse(&mut offen, &mut ueberfuellt, (50.0, -1.0), (51.0, -1.0));
+        assert_eq!(events[0].zeit, 50.0);
+
+        // Vollständig überströmt, sobald das Unterwasser die freigegebene Höhe 0.01 t übersteigt:
+        // -0.2 + 0.05 (t - 10) = 0.01 t bei t = 17.5 s
+        let mut offen = vec![FuellsystemStatus::StartOeffnung];
+        let events =
+            system.pruefe_ereignisse(&mut offen, &mut ueberfuellt, (10.0, -0.2), (20.0, 0.3));
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].status, FuellsystemStatus::VollUeberfuellt);
+        assert!((events[0].zeit - 17.5).abs() <= EREIGNISGENAUIGKEIT);
+        assert_eq!(ueberfuellt[0], FuellsystemStatus::VollUeberfuellt);
     }
 }
@@ -0,0 +1,37 @@
+/*!
+Berechnung des Füllens und Entleerens einer Schleusenkammer.
+
+Eine Schleuse wird aus der Eingabedatei gelesen ([`eingabe::read_schleusenwerte`]) und als
+[`hydraulic::Schleuse`] erzeugt. Die Simulation liefert je Zeitschritt einen
+[`hydraulic::Simulationsschritt`], der mit den Funktionen aus [`auswertung`] bewertet werden kann.
+Die Untersuchungen des Kommandozeilenprogramms stehen in [`studien`].
+
+```no_run
+use kammerwasserspiegel::auswertung::wasserspiegelneigung;
+use kammerwasserspiegel::eingabe::read_schleusenwerte;
+
+let werte = read_schleusenwerte("test.toml").unwrap();
+let schleuse = werte.erschaffe_schleuse();
+let schritte = schleuse.fuell_schleuse();
+println!("I_w = {} mm/m", wasserspiegelneigung(&schleuse, &schritte));
+```
+*/
+
+pub mod auswertung;
+pub mod bruteforce;
+pub mod eingabe;
+pub mod ersatzmodell;
+pub mod fileio;
+pub mod fortschritt;
+pub mod hydraulic;
+pub mod nachweis;
+pub mod optimierung;
+pub mod parameter;
+pub mod pareto;
+pub mod sicherung;
+pub mod studien;
+pub mod suche;
+pub mod versuchsplan;
+pub mod zeitintegration;
+pub mod zufall;
+pub mod zwischenspeicher;
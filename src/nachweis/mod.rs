@@ -0,0 +1,223 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::auswertung::*;
+use crate::hydraulic::*;
+
+/**
+Grenzwerte, die eine Füllung einhalten muss. Nicht angegebene Grenzen werden nicht geprüft.
+Alle Studien bewerten ihre Varianten mit denselben Nachweisen:
+
+```toml
+[nachweise]
+fuellzeit = 1260.0              # in s
+wasserspiegelneigung = 0.4      # in mm/m
+durchfluss = [-1.0, 58.26]      # in m³/s
+durchflusszunahme = [-0.7299, 0.1962] # in m³/s²
+vollgeoeffnet = true            # Alle Querschnitte öffnen vor Ende der Füllung ganz
+```
+*/
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct Nachweise {
+    pub fuellzeit: Option<f64>,
+    pub wasserspiegelneigung: Option<f64>,
+    pub durchfluss: Option<[f64; 2]>,
+    pub durchflusszunahme: Option<[f64; 2]>,
+    pub vollgeoeffnet: bool,
+    // Füllzeit und Wasserspiegelneigung genau auf der Grenze gelten als eingehalten, wie bisher bei max-speed
+    #[serde(skip)]
+    pub grenze_eingehalten: bool,
+}
+
+// Geprüfte Größe, die Nummer wird in den Ausgabedateien als maßgebende Grenze geschrieben
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Nachweis {
+    Fuellzeit = 1,
+    Wasserspiegelneigung = 2,
+    Durchfluss = 3,
+    Durchflusszunahme = 4,
+    Vollgeoeffnet = 5,
+}
+
+impl Nachweis {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Nachweis::Fuellzeit => "Füllzeit",
+            Nachweis::Wasserspiegelneigung => "Wasserspiegelneigung",
+            Nachweis::Durchfluss => "Durchfluss",
+            Nachweis::Durchflusszunahme => "Durchflusszunahme",
+            Nachweis::Vollgeoeffnet => "Vollständig geöffnete Querschnitte",
+        }
+    }
+
+    pub fn nummer(&self) -> u32 {
+        *self as u32
+    }
+}
+
+/**
+Nicht eingehaltene Grenze mit dem maßgebenden Wert der Simulation.
+*/
+#[derive(Clone, Debug)]
+pub struct Verletzung {
+    pub nachweis: Nachweis,
+    pub wert: f64,
+    pub grenze: f64,
+}
+
+impl Verletzung {
+    fn neu(nachweis: Nachweis, wert: f64, grenze: f64) -> Self {
+        Verletzung {
+            nachweis,
+            wert,
+            grenze,
+        }
+    }
+
+    // Betrag, um den die Grenze über- bzw. unterschritten ist
+    pub fn betrag(&self) -> f64 {
+        (self.wert - self.grenze).abs()
+    }
+
+    // Betrag bezogen auf die Grenze, damit verschiedene Nachweise vergleichbar sind
+    pub fn anteil(&self) -> f64 {
+        if self.grenze == 0.0 {
+            self.betrag()
+        } else {
+            self.betrag() / self.grenze.abs()
+        }
+    }
+}
+
+impl fmt::Display for Verletzung {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} = {} verletzt die Grenze {} um {}",
+            self.nachweis.name(),
+            self.wert,
+            self.grenze,
+            self.betrag()
+        )
+    }
+}
+
+impl Nachweise {
+    /**
+    Prüft die Simulation einer Füllung und gibt alle nicht eingehaltenen Grenzen zurück.
+    Füllzeit und Wasserspiegelneigung müssen unter ihrer Grenze bleiben, ein Wert genau auf der
+    Grenze gilt als verletzt, außer mit `grenze_eingehalten`. Durchfluss und Durchflusszunahme dürfen ihren Bereich einschließlich
+    der Grenzen ausschöpfen.
+    */
+    pub fn pruefe(&self, schl: &Schleuse, res: &[Simulationsschritt]) -> Vec<Verletzung> {
+        self.pruefe_kennwerte(&Kennwerte::berechne(schl, res))
+    }
+
+    // Wie `pruefe`, aber mit den bereits berechneten Kennwerten einer Füllung
+    pub fn pruefe_kennwerte(&self, k: &Kennwerte) -> Vec<Verletzung> {
+        let mut verletzungen = Vec::new();
+        let ueberschritten =
+            |wert: f64, grenze: f64| wert > grenze || (wert == grenze && !self.grenze_eingehalten);
+        if let Some(grenze) = self.fuellzeit {
+            if ueberschritten(k.fuellzeit, grenze) {
+                verletzungen.push(Verletzung::neu(Nachweis::Fuellzeit, k.fuellzeit, grenze));
+            }
+        }
+        if let Some(grenze) = self.wasserspiegelneigung {
+            if ueberschritten(k.wasserspiegelneigung, grenze) {
+                verletzungen.push(Verletzung::neu(
+                    Nachweis::Wasserspiegelneigung,
+                    k.wasserspiegelneigung,
+                    grenze,
+                ));
+            }
+        }
+        if let Some(bereich) = self.durchfluss {
+            let werte = (k.min_durchfluss, k.max_durchfluss);
+            pruefe_bereich(&mut verletzungen, Nachweis::Durchfluss, werte, bereich);
+        }
+        if let Some(bereich) = self.durchflusszunahme {
+            let werte = (k.min_durchflusszunahme, k.max_durchflusszunahme);
+            pruefe_bereich(
+                &mut verletzungen,
+                Nachweis::Durchflusszunahme,
+                werte,
+                bereich,
+            );
+        }
+        if self.vollgeoeffnet && k.fuelloeffnungen < k.fuellquerschnitte {
+            verletzungen.push(Verletzung::neu(
+                Nachweis::Vollgeoeffnet,
+                k.fuelloeffnungen as f64,
+                k.fuellquerschnitte as f64,
+            ));
+        }
+        verletzungen
+    }
+
+    // Ob überhaupt eine Grenze angegeben ist
+    pub fn ist_leer(&self) -> bool {
+        self.fuellzeit.is_none()
+            && self.wasserspiegelneigung.is_none()
+            && self.durchfluss.is_none()
+            && self.durchflusszunahme.is_none()
+            && !self.vollgeoeffnet
+    }
+
+    pub fn erfuellt(&self, schl: &Schleuse, res: &[Simulationsschritt]) -> bool {
+        self.pruefe(schl, res).is_empty()
+    }
+}
+
+// Die Verletzung mit dem größten Anteil an ihrer Grenze
+pub fn massgebend(verletzungen: &[Verletzung]) -> Option<&Verletzung> {
+    verletzungen
+        .iter()
+        .max_by(|a, b| a.anteil().total_cmp(&b.anteil()))
+}
+
+fn pruefe_bereich(
+    verletzungen: &mut Vec<Verletzung>,
+    nachweis: Nachweis,
+    (kleinster, groesster): (f64, f64),
+    [min, max]: [f64; 2],
+) {
+    if kleinster < min {
+        verletzungen.push(Verletzung::neu(nachweis, kleinster, min));
+    }
+    if groesster > max {
+        verletzungen.push(Verletzung::neu(nachweis, groesster, max));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wert_auf_der_grenze() {
+        let k = Kennwerte {
+            fuellzeit: 1260.0,
+            max_durchfluss: 58.26,
+            max_durchflusszunahme: 0.1,
+            wasserspiegelneigung: 0.3,
+            fuelloeffnungen: 4,
+            min_durchfluss: 0.0,
+            min_durchflusszunahme: -0.1,
+            fuellquerschnitte: 4,
+        };
+        let mut nachweise = Nachweise {
+            fuellzeit: Some(1260.0),
+            wasserspiegelneigung: Some(0.4),
+            durchfluss: Some([-1.0, 58.26]),
+            ..Nachweise::default()
+        };
+        let verletzungen = nachweise.pruefe_kennwerte(&k);
+        assert_eq!(verletzungen.len(), 1);
+        assert_eq!(verletzungen[0].nachweis, Nachweis::Fuellzeit);
+        assert_eq!(verletzungen[0].betrag(), 0.0);
+        nachweise.grenze_eingehalten = true;
+        assert!(nachweise.pruefe_kennwerte(&k).is_empty());
+    }
+}
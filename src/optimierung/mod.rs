@@ -0,0 +1,190 @@
+use std::cell::Cell;
+
+/**
+Einstellungen des Simplex-Verfahrens nach Nelder und Mead zur Minimierung ohne Ableitungen.
+Gerechnet wird im auf den Einheitswürfel normierten Kasten der Schranken, jeder Versuchspunkt
+wird auf den Kasten projiziert. Das Verfahren endet, wenn alle Ecken des Simplex näher als
+`toleranz` (Anteil an der Breite des Kastens) an der besten Ecke liegen oder nach `max_auswertungen`.
+Danach wird bis zu `neustarts` mal mit einem neuen Simplex um den besten Punkt neu begonnen,
+solange sich der Wert dabei verbessert.
+*/
+#[derive(Clone, Copy, Debug)]
+pub struct NelderMead {
+    pub max_auswertungen: usize,
+    pub toleranz: f64,
+    pub neustarts: u32,
+}
+
+impl Default for NelderMead {
+    fn default() -> Self {
+        NelderMead {
+            max_auswertungen: 1000,
+            toleranz: 1e-4,
+            neustarts: 2,
+        }
+    }
+}
+
+// Bester gefundener Punkt mit seinem Wert und der Anzahl der Auswertungen insgesamt
+#[derive(Clone, Debug)]
+pub struct Optimum {
+    pub punkt: Vec<f64>,
+    pub wert: f64,
+    pub auswertungen: usize,
+}
+
+impl NelderMead {
+    /**
+    Minimiert `f` zwischen `untere` und `obere`, beginnend bei `start`.
+    Ungültige Werte (NaN) von `f` gelten als unendlich groß.
+    */
+    pub fn minimiere(
+        &self,
+        untere: &[f64],
+        obere: &[f64],
+        start: &[f64],
+        f: impl Fn(&[f64]) -> f64,
+    ) -> Optimum {
+        let n = start.len();
+        let zurueck = |u: &[f64]| -> Vec<f64> {
+            u.iter()
+                .enumerate()
+                .map(|(i, u)| untere[i] + u.clamp(0.0, 1.0) * (obere[i] - untere[i]))
+                .collect()
+        };
+        let auswertungen = Cell::new(0);
+        let bewerte = |u: &[f64]| {
+            auswertungen.set(auswertungen.get() + 1);
+            let wert = f(&zurueck(u));
+            if wert.is_nan() {
+                f64::INFINITY
+            } else {
+                wert
+            }
+        };
+
+        let mut beste: Vec<f64> = start
+            .iter()
+            .enumerate()
+            .map(|(i, x)| {
+                if obere[i] > untere[i] {
+                    ((x - untere[i]) / (obere[i] - untere[i])).clamp(0.0, 1.0)
+                } else {
+                    0.0
+                }
+            })
+            .collect();
+        let mut bester_wert = bewerte(&beste);
+
+        for _ in 0..=self.neustarts {
+            // Anfangssimplex: Schritt von einem Viertel des Kastens je Richtung
+            let mut simplex = vec![(beste.clone(), bester_wert)];
+            for i in 0..n {
+                let mut u = beste.clone();
+                u[i] += if u[i] + 0.25 <= 1.0 { 0.25 } else { -0.25 };
+                let wert = bewerte(&u);
+                simplex.push((u, wert));
+            }
+
+            while auswertungen.get() < self.max_auswertungen {
+                simplex.sort_by(|a, b| a.1.total_cmp(&b.1));
+                let groesse = simplex[1..]
+                    .iter()
+                    .map(|(u, _)| abstand(u, &simplex[0].0))
+                    .fold(0.0, f64::max);
+                if groesse <= self.toleranz {
+                    break;
+                }
+
+                let schwerpunkt: Vec<f64> = (0..n)
+                    .map(|i| simplex[..n].iter().map(|(u, _)| u[i]).sum::<f64>() / n as f64)
+                    .collect();
+                let punkt = |t: f64| -> Vec<f64> {
+                    schwerpunkt
+                        .iter()
+                        .zip(&simplex[n].0)
+                        .map(|(s, w)| (s + t * (s - w)).clamp(0.0, 1.0))
+                        .collect()
+                };
+
+                let reflexion = punkt(1.0);
+                let wert_r = bewerte(&reflexion);
+                if wert_r < simplex[0].1 {
+                    let expansion = punkt(2.0);
+                    let wert_e = bewerte(&expansion);
+                    simplex[n] = if wert_e < wert_r {
+                        (expansion, wert_e)
+                    } else {
+                        (reflexion, wert_r)
+                    };
+                } else if wert_r < simplex[n - 1].1 {
+                    simplex[n] = (reflexion, wert_r);
+                } else {
+                    let kontraktion = if wert_r < simplex[n].1 {
+                        punkt(0.5)
+                    } else {
+                        punkt(-0.5)
+                    };
+                    let wert_k = bewerte(&kontraktion);
+                    if wert_k < simplex[n].1.min(wert_r) {
+                        simplex[n] = (kontraktion, wert_k);
+                    } else {
+                        // Schrumpfen auf die beste Ecke
+                        let erste = simplex[0].0.clone();
+                        for (u, wert) in simplex[1..].iter_mut() {
+                            for (x, b) in u.iter_mut().zip(&erste) {
+                                *x = b + 0.5 * (*x - b);
+                            }
+                            *wert = bewerte(u);
+                        }
+                    }
+                }
+            }
+
+            let (u, wert) = simplex
+                .into_iter()
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+                .unwrap();
+            let verbessert = wert < bester_wert;
+            if wert <= bester_wert {
+                beste = u;
+                bester_wert = wert;
+            }
+            if !verbessert || auswertungen.get() >= self.max_auswertungen {
+                break;
+            }
+        }
+
+        Optimum {
+            punkt: zurueck(&beste),
+            wert: bester_wert,
+            auswertungen: auswertungen.get(),
+        }
+    }
+}
+
+fn abstand(a: &[f64], b: &[f64]) -> f64 {
+    a.iter()
+        .zip(b)
+        .map(|(x, y)| (x - y).powi(2))
+        .sum::<f64>()
+        .sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn findet_minimum_einer_verschobenen_quadratischen_funktion() {
+        let verfahren = NelderMead {
+            toleranz: 1e-8,
+            ..NelderMead::default()
+        };
+        let f = |x: &[f64]| (x[0] - 0.3).powi(2) + 2.0 * (x[1] + 1.2).powi(2) + 5.0;
+        let optimum = verfahren.minimiere(&[-2.0, -2.0], &[2.0, 2.0], &[1.0, 1.0], f);
+        assert!((optimum.punkt[0] - 0.3).abs() < 1e-4);
+        assert!((optimum.punkt[1] + 1.2).abs() < 1e-4);
+        assert!((optimum.wert - 5.0).abs() < 1e-8);
+    }
+}
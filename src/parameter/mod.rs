@@ -0,0 +1,231 @@
+use toml::Value;
+
+use crate::eingabe::*;
+
+/**
+Zugriff auf die Zahlenwerte einer Eingabe über ihren Pfad, wie er in der Eingabedatei steht,
+etwa `unterwasser`, `fuellsystem[2].startzeit`, `fuellsystem[0].querschnitt.breite`
+oder `entleersystem[1].verluste.einlauf`. Studien können so beliebige Eingaben variieren.
+*/
+pub fn lese_parameter(werte: &Schleusenwerte, pfad: &str) -> Result<f64, String> {
+    let baum = als_baum(werte)?;
+    match suche_wert(&baum, pfad)? {
+        Value::Float(w) => Ok(*w),
+        Value::Integer(w) => Ok(*w as f64),
+        _ => Err(format!("{} ist keine Zahl", pfad)),
+    }
+}
+
+/**
+Gibt eine Kopie der Eingabe zurück, in der die angegebenen Pfade die neuen Werte erhalten.
+Die geänderte Eingabe wird wie die Eingabedatei geprüft (`Schleusenwerte::pruefe`).
+*/
+pub fn setze_parameter(
+    werte: &Schleusenwerte,
+    aenderungen: &[(String, f64)],
+) -> Result<Schleusenwerte, String> {
+    let mut baum = als_baum(werte)?;
+    for (pfad, neu) in aenderungen {
+        let wert = suche_wert_mut(&mut baum, pfad)?;
+        *wert = match wert {
+            Value::Float(_) => Value::Float(*neu),
+            Value::Integer(_) => Value::Integer(neu.round() as i64),
+            _ => return Err(format!("{} ist keine Zahl", pfad)),
+        };
+    }
+    let mut geaendert: Schleusenwerte = baum
+        .try_into()
+        .map_err(|why| format!("Ungültige Eingabe nach Änderung: {}", why))?;
+    geaendert
+        .pruefe("changed input")
+        .map_err(|why| format!("Ungültige Eingabe nach Änderung: {}", why))?;
+    Ok(geaendert)
+}
+
+/**
+Pfade aller Zahlenwerte, die die Schleuse selbst beschreiben: Wasserstände, Abmessungen,
+Querschnitte und Verlustbeiwerte. Zeitintegration und die Angaben der Studien gehören nicht dazu.
+Nicht angegebene Verlustbeiwerte erscheinen erst nach `mit_allen_verlusten`.
+*/
+pub fn eingabeparameter(werte: &Schleusenwerte) -> Vec<String> {
+    let mut pfade = Vec::new();
+    if let Ok(Value::Table(tabelle)) = als_baum(werte) {
+        for (name, wert) in &tabelle {
+            let studie = [
+                "zeitintegration",
+                "nachweise",
+                "unsicherheiten",
+                "versuchsplan",
+                "variation",
+            ];
+            if !studie.contains(&name.as_str()) {
+                sammle_pfade(wert, name.clone(), &mut pfade);
+            }
+        }
+    }
+    pfade
+}
+
+/**
+Gibt eine Kopie zurück, in der bei jedem Querschnitt alle Verlustbeiwerte ausdrücklich
+angegeben sind, damit sie über ihren Pfad erreichbar sind. Die Rechenergebnisse bleiben gleich.
+Beiwerte, die der Querschnitt nicht verwendet, bleiben weg.
+*/
+pub fn mit_allen_verlusten(werte: &Schleusenwerte) -> Schleusenwerte {
+    let mut werte = werte.clone();
+    for q in werte
+        .fuellsystem
+        .iter_mut()
+        .chain(werte.entleersystem.iter_mut())
+    {
+        let beiwerte = q.verluste.beiwerte();
+        let unbenutzt = q.querschnitt.unbenutzte_verluste(q.schuetz);
+        let benutzt = |name: &str| !unbenutzt.contains(&name);
+        q.verluste = Verlustwerte {
+            vorlage: None,
+            einlauf: benutzt("einlauf").then_some(beiwerte.einlauf),
+            aufweitung: benutzt("aufweitung").then_some(beiwerte.aufweitung),
+            austritt: benutzt("austritt").then_some(beiwerte.austritt),
+            ueberfall: benutzt("ueberfall").then_some(beiwerte.ueberfall),
+            kontraktion: benutzt("kontraktion").then_some(beiwerte.kontraktion),
+            umlenkung: benutzt("umlenkung").then_some(beiwerte.umlenkung),
+        };
+    }
+    werte
+}
+
+/**
+Ersetzt jedes `[*]` im Pfad durch alle Indizes der Liste an dieser Stelle,
+aus `fuellsystem[*].startzeit` wird `fuellsystem[0].startzeit`, `fuellsystem[1].startzeit`, ...
+*/
+pub fn erweitere_pfad(werte: &Schleusenwerte, pfad: &str) -> Result<Vec<String>, String> {
+    let baum = als_baum(werte)?;
+    erweitere(&baum, pfad)
+}
+
+fn erweitere(baum: &Value, pfad: &str) -> Result<Vec<String>, String> {
+    let (vorne, hinten) = match pfad.split_once("[*]") {
+        Some(teile) => teile,
+        None => return Ok(vec![pfad.to_string()]),
+    };
+    let anzahl = match suche_wert(baum, vorne)? {
+        Value::Array(liste) => liste.len(),
+        _ => return Err(format!("{} ist keine Liste", vorne)),
+    };
+    let mut pfade = Vec::new();
+    for i in 0..anzahl {
+        pfade.append(&mut erweitere(
+            baum,
+            &format!("{}[{}]{}", vorne, i, hinten),
+        )?);
+    }
+    Ok(pfade)
+}
+
+fn als_baum(werte: &Schleusenwerte) -> Result<Value, String> {
+    Value::try_from(werte).map_err(|why| format!("Eingabe nicht darstellbar: {}", why))
+}
+
+fn sammle_pfade(wert: &Value, pfad: String, pfade: &mut Vec<String>) {
+    match wert {
+        Value::Float(_) | Value::Integer(_) => pfade.push(pfad),
+        Value::Array(liste) => {
+            for (i, w) in liste.iter().enumerate() {
+                sammle_pfade(w, format!("{}[{}]", pfad, i), pfade);
+            }
+        }
+        Value::Table(tabelle) => {
+            for (name, w) in tabelle {
+                sammle_pfade(w, format!("{}.{}", pfad, name), pfade);
+            }
+        }
+        _ => {}
+    }
+}
+
+// Teil eines Pfads: Name eines Feldes oder Index in einer Liste
+enum Schritt<'a> {
+    Schluessel(&'a str),
+    Index(usize),
+}
+
+// Zerlegt `fuellsystem[2].startzeit` in Schlüssel und Indizes
+fn zerlege(pfad: &str) -> Result<Vec<Schritt<'_>>, String> {
+    let mut schritte = Vec::new();
+    for teil in pfad.split('.') {
+        let (name, rest) = match teil.find('[') {
+            Some(i) => teil.split_at(i),
+            None => (teil, ""),
+        };
+        if name.is_empty() {
+            return Err(format!("Ungültiger Pfad {}", pfad));
+        }
+        schritte.push(Schritt::Schluessel(name));
+        for index in rest.split_terminator(']') {
+            let index = index
+                .strip_prefix('[')
+                .and_then(|i| i.parse().ok())
+                .ok_or_else(|| format!("Ungültiger Index in {}", pfad))?;
+            schritte.push(Schritt::Index(index));
+        }
+    }
+    Ok(schritte)
+}
+
+fn suche_wert<'a>(baum: &'a Value, pfad: &str) -> Result<&'a Value, String> {
+    let mut wert = baum;
+    for schritt in zerlege(pfad)? {
+        wert = match schritt {
+            Schritt::Schluessel(name) => wert.get(name),
+            Schritt::Index(i) => wert.get(i),
+        }
+        .ok_or_else(|| format!("{} ist in der Eingabe nicht vorhanden", pfad))?;
+    }
+    Ok(wert)
+}
+
+fn suche_wert_mut<'a>(baum: &'a mut Value, pfad: &str) -> Result<&'a mut Value, String> {
+    let mut wert = baum;
+    for schritt in zerlege(pfad)? {
+        wert = match schritt {
+            Schritt::Schluessel(name) => wert.get_mut(name),
+            Schritt::Index(i) => wert.get_mut(i),
+        }
+        .ok_or_else(|| format!("{} ist in der Eingabe nicht vorhanden", pfad))?;
+    }
+    Ok(wert)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn geaenderte_eingabe_wird_geprueft() {
+        let mut werte = read_schleusenwerte("test.toml").unwrap();
+        werte.fuellsystem[0].querschnitt = Querschnittswerte::RechteckGesetz {
+            breite: 2.3,
+            hoehe: 0.35,
+            oeffnung: vec![[0.0, 0.0], [60.0, 0.35]],
+            oeffnung_csv: None,
+        };
+        let setze = |werte: &Schleusenwerte, pfad: &str, wert: f64| {
+            setze_parameter(werte, &[(pfad.to_string(), wert)])
+        };
+        let zeit = "fuellsystem[0].querschnitt.oeffnung[1][0]";
+        let hoehe = "fuellsystem[0].querschnitt.oeffnung[1][1]";
+        assert!(setze(&werte, hoehe, -0.1).is_err());
+        assert!(setze(&werte, zeit, 0.0).is_err());
+        // Die Stützstellen werden wieder nach der Zeit sortiert
+        let geaendert = setze(&werte, "fuellsystem[0].querschnitt.oeffnung[0][0]", 90.0).unwrap();
+        match &geaendert.fuellsystem[0].querschnitt {
+            Querschnittswerte::RechteckGesetz { oeffnung, .. } => {
+                assert_eq!(oeffnung, &vec![[60.0, 0.35], [90.0, 0.0]])
+            }
+            _ => panic!("Hubgesetz erwartet"),
+        }
+        // Ohne Schütz wird die Einschnürung nicht verwendet
+        werte.fuellsystem[1].verluste.kontraktion = Some(0.6);
+        assert!(setze(&werte, "unterwasser", 1.0).is_err());
+    }
+}
@@ -0,0 +1,19 @@
+/**
+Indizes der nicht dominierten Punkte, alle Ziele werden minimiert.
+Ein Punkt ist dominiert, wenn ein anderer in keinem Ziel schlechter und in mindestens einem besser ist.
+Punkte mit NaN in einem Ziel gehören nie zur Front. Die Reihenfolge der Eingabe bleibt erhalten.
+*/
+pub fn nicht_dominiert(ziele: &[Vec<f64>]) -> Vec<usize> {
+    let dominiert = |a: &[f64], b: &[f64]| {
+        a.iter().zip(b).all(|(x, y)| x <= y) && a.iter().zip(b).any(|(x, y)| x < y)
+    };
+    (0..ziele.len())
+        .filter(|&i| !ziele[i].iter().any(|z| z.is_nan()))
+        .filter(|&i| {
+            !ziele
+                .iter()
+                .enumerate()
+                .any(|(j, z)| j != i && dominiert(z, &ziele[i]))
+        })
+        .collect()
+}
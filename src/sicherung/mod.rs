@@ -0,0 +1,142 @@
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::prelude::*;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use log::{error, info, warn};
+
+/**
+Sicherungsdatei einer langen Studie. Jedes fertige Ergebnis wird sofort als Zeile
+`schluessel,wert,wert,...` angehängt, sodass ein abgebrochener Lauf beim nächsten Start
+an derselben Stelle weitermacht. Die erste Zeile enthält die Kennung der Studie; passt sie
+nicht zur aktuellen Studie (andere Eingabe oder andere Bereiche), wird von vorn begonnen.
+Eine beim Abbruch nicht abgeschlossene letzte Zeile wird übergangen.
+*/
+pub struct Sicherung {
+    datei: PathBuf,
+    fertig: HashMap<usize, Vec<f64>>,
+    schreiber: Option<Mutex<File>>,
+}
+
+impl Sicherung {
+    pub fn oeffne(datei: &Path, kennung: &str) -> Sicherung {
+        let kopf = format!("# {}", kennung);
+        let mut fertig = HashMap::new();
+        if let Ok(text) = std::fs::read_to_string(datei) {
+            // Nur Zeilen, die mit einem Zeilenumbruch abgeschlossen sind
+            let text = &text[..text.rfind('\n').map_or(0, |i| i + 1)];
+            let mut zeilen = text.lines();
+            if zeilen.next() == Some(kopf.as_str()) {
+                for zeile in zeilen {
+                    if let Some((schluessel, werte)) = lies_zeile(zeile) {
+                        fertig.insert(schluessel, werte);
+                    }
+                }
+                info!(
+                    "Resuming from {} with {} results",
+                    datei.display(),
+                    fertig.len()
+                );
+            } else {
+                warn!(
+                    "{} belongs to a different study, starting over",
+                    datei.display()
+                );
+            }
+        }
+
+        // Die Datei wird neu geschrieben, damit eine abgeschnittene Zeile nicht mitten im Text steht.
+        // Erst in eine temporäre Datei, die die alte ersetzt, damit ein Abbruch dabei nichts verliert
+        let mut temporaer = datei.as_os_str().to_owned();
+        temporaer.push(".tmp");
+        let temporaer = PathBuf::from(temporaer);
+        let schreiber = File::create(&temporaer)
+            .and_then(|mut file| {
+                let mut text = kopf;
+                text.push('\n');
+                for (schluessel, werte) in &fertig {
+                    text.push_str(&schreibe_zeile(*schluessel, werte));
+                }
+                file.write_all(text.as_bytes())?;
+                file.sync_all()
+            })
+            .and_then(|_| std::fs::rename(&temporaer, datei))
+            .and_then(|_| OpenOptions::new().append(true).open(datei));
+        let schreiber = match schreiber {
+            Ok(file) => Some(Mutex::new(file)),
+            Err(why) => {
+                error!("Couldn't write checkpoint {}: {}", datei.display(), why);
+                None
+            }
+        };
+        Sicherung {
+            datei: datei.to_path_buf(),
+            fertig,
+            schreiber,
+        }
+    }
+
+    // Gespeichertes Ergebnis zum Schlüssel aus einem früheren Lauf
+    pub fn ergebnis(&self, schluessel: usize) -> Option<&[f64]> {
+        self.fertig.get(&schluessel).map(|w| w.as_slice())
+    }
+
+    pub fn anzahl(&self) -> usize {
+        self.fertig.len()
+    }
+
+    pub fn speichere(&self, schluessel: usize, werte: &[f64]) {
+        if let Some(schreiber) = &self.schreiber {
+            let mut file = schreiber.lock().unwrap_or_else(|e| e.into_inner());
+            if let Err(why) = file.write_all(schreibe_zeile(schluessel, werte).as_bytes()) {
+                error!(
+                    "Couldn't write checkpoint {}: {}",
+                    self.datei.display(),
+                    why
+                );
+            }
+        }
+    }
+
+    /**
+    Liefert das gespeicherte Ergebnis zum Schlüssel oder berechnet und speichert es.
+    */
+    pub fn oder_berechne<const N: usize>(
+        &self,
+        schluessel: usize,
+        berechne: impl FnOnce() -> [f64; N],
+    ) -> [f64; N] {
+        if let Some(Ok(werte)) = self.ergebnis(schluessel).map(<[f64; N]>::try_from) {
+            return werte;
+        }
+        let werte = berechne();
+        self.speichere(schluessel, &werte);
+        werte
+    }
+
+    // Löscht die Sicherung, nachdem die Ergebnisse vollständig geschrieben sind
+    pub fn abschliessen(self) {
+        drop(self.schreiber);
+        if let Err(why) = std::fs::remove_file(&self.datei) {
+            error!("Couldn't remove {}: {}", self.datei.display(), why);
+        }
+    }
+}
+
+fn schreibe_zeile(schluessel: usize, werte: &[f64]) -> String {
+    let mut zeile = schluessel.to_string();
+    for w in werte {
+        zeile.push(',');
+        zeile.push_str(&w.to_string());
+    }
+    zeile.push('\n');
+    zeile
+}
+
+fn lies_zeile(zeile: &str) -> Option<(usize, Vec<f64>)> {
+    let mut teile = zeile.split(',');
+    let schluessel = teile.next()?.parse().ok()?;
+    let werte: Result<Vec<f64>, _> = teile.map(|w| w.parse()).collect();
+    Some((schluessel, werte.ok()?))
+}
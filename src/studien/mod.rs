@@ -0,0 +1,1106 @@
+use std::path::Path;
+
+use log::{error, info, warn};
+use rayon::prelude::*;
+use serde::Serialize;
+
+use crate::auswertung::*;
+use crate::eingabe::*;
+use crate::ersatzmodell::*;
+use crate::fileio::{lies_zahlentabelle, Ausgabe, JsonZeilen, Tabelle, Zahlentabelle, Zelle};
+use crate::fortschritt::Fortschritt;
+use crate::hydraulic::*;
+use crate::nachweis::*;
+use crate::optimierung::NelderMead;
+use crate::parameter::*;
+use crate::pareto::nicht_dominiert;
+use crate::sicherung::Sicherung;
+use crate::suche::Suche;
+use crate::versuchsplan::kombinationen;
+use crate::zeitintegration::Verfahren;
+use crate::zufall::*;
+use crate::zwischenspeicher::zwischenspeicher;
+
+// Nicht eingehaltene Nachweise beim Füllen mit den angegebenen Abmessungen
+fn pruefe_fuellung(
+    schleuse: &Schleusenwerte,
+    nachweise: &Nachweise,
+    hoehe: f64,
+    breite: f64,
+    geschwi: f64,
+) -> Vec<Verletzung> {
+    let k = zwischenspeicher().kennwerte(&schleuse.mit_rechteck(hoehe, breite, geschwi));
+    nachweise.pruefe_kennwerte(&k)
+}
+
+// Nummer des maßgebenden Nachweises und Betrag seiner Verletzung, 0 und 0 ohne Verletzung
+fn massgebende_spalten(verletzungen: &[Verletzung]) -> [f64; 2] {
+    match massgebend(verletzungen) {
+        Some(v) => [v.nachweis.nummer() as f64, v.betrag()],
+        None => [0.0, 0.0],
+    }
+}
+
+/**
+Raster über Höhe und Breite der rechteckigen Füllquerschnitte für zehn Öffnungsgeschwindigkeiten.
+Je Geschwindigkeit wird `dimenXXX.csv` mit Höhe, Breite, Füllzeit, Wasserspiegelneigung,
+Anzahl der Vollöffnungen, dem maßgebenden Nachweis und dem Betrag seiner Verletzung
+(0 für zulässige Varianten, siehe `Nachweis`) sowie der Öffnungsgeschwindigkeit geschrieben.
+Die Geschwindigkeit steht am Ende, damit die Spalten der vorhandenen Plotskripte gleich bleiben.
+*/
+pub fn ausprobieren(
+    schleuse: Schleusenwerte,
+    vgesch: (f64, f64),
+    vhoehe: (f64, f64),
+    vbreite: (f64, f64),
+    nachweise: &Nachweise,
+    ausgabe: &Ausgabe,
+) {
+    let var_geschwindigkeit = vgesch;
+    let var_hoehe = vhoehe;
+    let var_breite = vbreite;
+    let sicherung = oeffne_sicherung(
+        ausgabe,
+        "dimen",
+        &schleuse,
+        format!("{:?} {:?} {:?} {:?}", vgesch, vhoehe, vbreite, nachweise),
+    );
+    let fortschritt = Fortschritt::neu("Raster", 10 * 50 * 50, sicherung.anzahl());
+
+    for v in (0..100).step_by(10) {
+        let geschwi = var_geschwindigkeit.0
+            + (var_geschwindigkeit.1 - var_geschwindigkeit.0) * v as f64 / 100.0;
+        let results: Vec<[f64; 8]> = raster(0..100, 0..100)
+            .into_par_iter()
+            .map(|(i, j)| {
+                sicherung.oder_berechne((v * 10000 + i * 100 + j) as usize, || {
+                    let hoehe = var_hoehe.0 + (var_hoehe.1 - var_hoehe.0) * i as f64 / 100.0;
+                    let breite = var_breite.0 + (var_breite.1 - var_breite.0) * j as f64 / 100.0;
+                    let k = zwischenspeicher()
+                        .kennwerte(&schleuse.mit_rechteck(hoehe, breite, geschwi));
+                    let [nachweis, verletzung] =
+                        massgebende_spalten(&nachweise.pruefe_kennwerte(&k));
+                    fortschritt.schritt();
+                    [
+                        hoehe,
+                        breite,
+                        k.fuellzeit,
+                        k.wasserspiegelneigung,
+                        k.fuelloeffnungen as f64,
+                        nachweis,
+                        verletzung,
+                        geschwi,
+                    ]
+                })
+            })
+            .collect();
+        // Finden des minimums
+        {
+            let mut min = f64::INFINITY;
+            let mut index_min = 0;
+            for (c, r) in results.iter().enumerate() {
+                if min > r[0] && r[5] == 0.0 {
+                    min = r[0];
+                    index_min = c;
+                }
+            }
+            println!(
+                "Minimale Höhe bei with v = {} m/s : h = {}, b = {}",
+                geschwi, results[index_min][0], results[index_min][1]
+            )
+        }
+        //
+        let mut tabelle = Tabelle::neu([
+            ("hoehe", "m"),
+            ("breite", "m"),
+            ("fuellzeit", "s"),
+            ("wasserspiegelneigung", "mm/m"),
+            ("fuelloeffnungen", ""),
+            ("nachweis", ""),
+            ("verletzung", ""),
+            ("geschwindigkeit", "m/s"),
+        ]);
+        for f in &results {
+            tabelle.zeile(*f);
+        }
+        ausgabe.schreibe_tabelle(&format!("dimen{:03}.csv", v), &tabelle);
+    }
+    fortschritt.beende();
+    sicherung.abschliessen();
+}
+
+/**
+Simuliert Füllen und, falls ein Entleerungssystem vorhanden ist, Entleeren der Schleuse
+und schreibt Zeitreihen und Ereignisse nach `result.csv`/`events.csv` bzw. `result_entleerung.csv`/`events_entleerung.csv`.
+Mit `ausgabe.json` steht der ganze Lauf mit Eingabe, Zeitreihen, Ereignissen und Kennwerten in `result.json`,
+mit `ausgabe.json_zeilen` wird jeder Vorgang schon während der Rechnung nach `result.jsonl` bzw.
+`result_entleerung.jsonl` geschrieben (siehe `Zeile`).
+*/
+pub fn simuliere_schleuse(werte: &Schleusenwerte, ausgabe: &Ausgabe) {
+    let schl = werte.erschaffe_schleuse();
+    info!("Durchrechnen der Schleuse");
+    let mut zeilen = beginne_zeilen(ausgabe, "result.jsonl", werte);
+    let gefuellt = schl.fuell_schleuse_mit(|s| schreibe_schritt(&mut zeilen, s));
+    let fuellung = schreibe_simulation(
+        &schl,
+        &gefuellt,
+        schl.fuellsystem.anzahl_fuellsysteme(),
+        ausgabe,
+        ("result.csv", "events.csv"),
+        zeilen,
+    );
+
+    let entleert;
+    let entleerung = match schl.unterhaupt.entleersystem.anzahl_fuellsysteme() {
+        0 => None,
+        querschnitte => {
+            info!("Durchrechnen der Entleerung");
+            let mut zeilen = beginne_zeilen(ausgabe, "result_entleerung.jsonl", werte);
+            entleert = schl.entleere_schleuse_mit(|s| schreibe_schritt(&mut zeilen, s));
+            Some(schreibe_simulation(
+                &schl,
+                &entleert,
+                querschnitte,
+                ausgabe,
+                ("result_entleerung.csv", "events_entleerung.csv"),
+                zeilen,
+            ))
+        }
+    };
+    if ausgabe.json {
+        ausgabe.schreibe_json(
+            "result.json",
+            &Lauf {
+                eingabe: werte,
+                fuellung,
+                entleerung,
+            },
+        );
+    }
+}
+
+// Vollständiger Lauf von `simuliere_schleuse` für `result.json`
+#[derive(Serialize)]
+struct Lauf<'a> {
+    eingabe: &'a Schleusenwerte,
+    fuellung: Vorgang<'a>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    entleerung: Option<Vorgang<'a>>,
+}
+
+// Füllen oder Entleeren, die Kennwerte beziehen sich auf den jeweiligen Vorgang
+#[derive(Serialize)]
+struct Vorgang<'a> {
+    kennwerte: Kennwerte,
+    zeitreihe: &'a [Simulationsschritt],
+    ereignisse: Vec<&'a Event>,
+}
+
+/**
+Zeile in `result.jsonl`, unterschieden durch das Feld `typ`. Zuerst kommt die Eingabe,
+dann jeder Zeitschritt, gefolgt von seinen Ereignissen, zuletzt die Kennwerte des Vorgangs.
+*/
+#[derive(Serialize)]
+#[serde(tag = "typ", rename_all = "lowercase")]
+enum Zeile<'a> {
+    Eingabe(&'a Schleusenwerte),
+    Schritt(&'a Simulationsschritt),
+    Ereignis(&'a Event),
+    Kennwerte(&'a Kennwerte),
+}
+
+fn beginne_zeilen(ausgabe: &Ausgabe, name: &str, werte: &Schleusenwerte) -> Option<JsonZeilen> {
+    if !ausgabe.json_zeilen {
+        return None;
+    }
+    let mut zeilen = ausgabe.json_zeilen(name);
+    zeilen.schreibe(&Zeile::Eingabe(werte));
+    Some(zeilen)
+}
+
+fn schreibe_schritt(zeilen: &mut Option<JsonZeilen>, schritt: &Simulationsschritt) {
+    if let Some(zeilen) = zeilen {
+        zeilen.schreibe(&Zeile::Schritt(schritt));
+        for event in &schritt.events {
+            zeilen.schreibe(&Zeile::Ereignis(event));
+        }
+    }
+}
+
+fn schreibe_simulation<'a>(
+    schl: &Schleuse,
+    v: &'a [Simulationsschritt],
+    querschnitte: usize,
+    ausgabe: &Ausgabe,
+    (result_name, events_name): (&str, &str),
+    zeilen: Option<JsonZeilen>,
+) -> Vorgang<'a> {
+    let mut events = Tabelle::neu([("zeit", "s"), ("ereignis", "")]);
+    for k in v {
+        for event in &k.events {
+            events.zeile([Zelle::from(event.zeit), Zelle::from(event.desc.as_str())]);
+        }
+    }
+    info!("Auswerten der Ergebnisse");
+    let kennwerte = Kennwerte {
+        fuellquerschnitte: querschnitte,
+        ..Kennwerte::berechne(schl, v)
+    };
+    println!("I_w = {} mm/m", kennwerte.wasserspiegelneigung);
+    let mut result = Tabelle::neu([
+        ("iteration", ""),
+        ("zeit", "s"),
+        ("kammerwasserspiegel", "m"),
+        ("durchfluss", "m³/s"),
+        ("durchflusszunahme", "m³/s²"),
+    ]);
+    for i in v {
+        result.zeile([
+            i.iteration as f64,
+            i.zeitschritt,
+            i.kammerwasserspiegel,
+            i.durchfluss,
+            i.durchflusszunahme,
+        ]);
+    }
+    ausgabe.schreibe_tabelle(events_name, &events);
+    ausgabe.schreibe_tabelle(result_name, &result);
+    if let Some(mut zeilen) = zeilen {
+        zeilen.schreibe(&Zeile::Kennwerte(&kennwerte));
+        zeilen.beende();
+    }
+    Vorgang {
+        kennwerte,
+        zeitreihe: v,
+        ereignisse: v.iter().flat_map(|k| &k.events).collect(),
+    }
+}
+
+/**
+Kleinste zulässige Höhe je Breite und Öffnungsgeschwindigkeit, geschrieben nach `min.csv`.
+Ist im Bereich keine Höhe zulässig, steht NaN in der Datei und dahinter der maßgebende Nachweis
+bei der größten Höhe mit dem Betrag seiner Verletzung, sonst zweimal 0.
+*/
+pub fn minimiere_hoehe_und_geschwi(
+    schleuse: Schleusenwerte,
+    vgesch: (f64, f64),
+    vbreite: (f64, f64),
+    vhoehe: (f64, f64),
+    nachweise: &Nachweise,
+    suche: &Suche,
+    ausgabe: &Ausgabe,
+) {
+    let mut paare = Vec::new();
+    for v in (0..100).step_by(10) {
+        for i in (0..100).step_by(2) {
+            paare.push((v, i));
+        }
+    }
+    let sicherung = oeffne_sicherung(
+        ausgabe,
+        "min",
+        &schleuse,
+        format!(
+            "{:?} {:?} {:?} {:?} {:?}",
+            vgesch, vbreite, vhoehe, nachweise, suche
+        ),
+    );
+    let fortschritt = Fortschritt::neu("Minimierung", paare.len(), sicherung.anzahl());
+    let results: Vec<[f64; 5]> = paare
+        .into_par_iter()
+        .map(|(v, i)| {
+            sicherung.oder_berechne(v * 100 + i, || {
+                let geschwi = vgesch.0 + (vgesch.1 - vgesch.0) * v as f64 / 100.0;
+                let breite = vbreite.0 + (vbreite.1 - vbreite.0) * i as f64 / 100.0;
+                let mut grund = [0.0, 0.0];
+                let min_hoehe = suche
+                    .erster_zulaessiger(vhoehe.0, vhoehe.1, |hoehe| {
+                        pruefe_fuellung(&schleuse, nachweise, hoehe, breite, geschwi).is_empty()
+                    })
+                    .unwrap_or_else(|| {
+                        let verletzungen =
+                            pruefe_fuellung(&schleuse, nachweise, vhoehe.1, breite, geschwi);
+                        if let Some(v) = massgebend(&verletzungen) {
+                            info!(
+                                "Keine zulässige Höhe für b = {}, v = {}: {}",
+                                breite, geschwi, v
+                            );
+                        }
+                        grund = massgebende_spalten(&verletzungen);
+                        f64::NAN
+                    });
+                fortschritt.schritt();
+                [breite, min_hoehe, geschwi, grund[0], grund[1]]
+            })
+        })
+        .collect();
+
+    let mut tabelle = Tabelle::neu([
+        ("breite", "m"),
+        ("hoehe", "m"),
+        ("geschwindigkeit", "m/s"),
+        ("nachweis", ""),
+        ("verletzung", ""),
+    ]);
+    for f in results {
+        tabelle.zeile(f);
+    }
+    ausgabe.schreibe_tabelle("min.csv", &tabelle);
+    fortschritt.beende();
+    sicherung.abschliessen();
+}
+
+/**
+Kleinste und größte zulässige Öffnungsgeschwindigkeit je Höhe und Breite, geschrieben nach
+`inter_min.csv` und `inter_max.csv`. Ist keine Geschwindigkeit zulässig, stehen in den letzten Spalten
+die Nummer des maßgebenden Nachweises, etwa 1 für die Füllzeit bzw. 2 für die Wasserspiegelneigung,
+und der Betrag seiner Verletzung.
+Die Grenzen werden mit `suche` eingegrenzt.
+*/
+pub fn interaktions_diagramm(
+    schleuse: Schleusenwerte,
+    vgesch: (f64, f64),
+    vbreite: (f64, f64),
+    vhoehe: (f64, f64),
+    nachweise: &Nachweise,
+    suche: &Suche,
+    ausgabe: &Ausgabe,
+) {
+    let sicherung = oeffne_sicherung(
+        ausgabe,
+        "inter",
+        &schleuse,
+        format!(
+            "{:?} {:?} {:?} {:?} {:?}",
+            vgesch, vbreite, vhoehe, nachweise, suche
+        ),
+    );
+    let fortschritt = Fortschritt::neu("Interaktionsdiagramm", 50 * 50, sicherung.anzahl());
+    let (results_min, results_max): (Vec<[f64; 5]>, Vec<[f64; 5]>) = raster(0..100, 0..100)
+        .into_par_iter()
+        .map(|(i, j)| {
+            let z = sicherung.oder_berechne((i * 100 + j) as usize, || {
+                let breite = vbreite.0 + (vbreite.1 - vbreite.0) * i as f64 / 100.0;
+                let hoehe = vhoehe.0 + (vhoehe.1 - vhoehe.0) * j as f64 / 100.0;
+                let zulaessig = |geschwi: f64| {
+                    pruefe_fuellung(&schleuse, nachweise, hoehe, breite, geschwi).is_empty()
+                };
+                // Maßgebende Grenze, wenn auch am Ende des Bereichs keine Geschwindigkeit zulässig ist
+                let grund = |geschwi: f64| {
+                    let verletzungen =
+                        pruefe_fuellung(&schleuse, nachweise, hoehe, breite, geschwi);
+                    if let Some(v) = massgebend(&verletzungen) {
+                        info!(
+                            "Keine zulässige Geschwindigkeit für b = {}, h = {}: {}",
+                            breite, hoehe, v
+                        );
+                    }
+                    massgebende_spalten(&verletzungen)
+                };
+                let mut reason = [0.0, 0.0];
+                let min_geschwi = suche
+                    .erster_zulaessiger(vgesch.0, vgesch.1, zulaessig)
+                    .unwrap_or_else(|| {
+                        reason = grund(vgesch.1);
+                        f64::NAN
+                    });
+                let reason_min = reason;
+                let max_geschwi = suche
+                    .erster_zulaessiger(vgesch.1, vgesch.0, zulaessig)
+                    .unwrap_or_else(|| {
+                        reason = grund(vgesch.0);
+                        f64::NAN
+                    });
+                fortschritt.schritt();
+                [
+                    breite,
+                    hoehe,
+                    min_geschwi,
+                    reason_min[0],
+                    reason_min[1],
+                    max_geschwi,
+                    reason[0],
+                    reason[1],
+                ]
+            });
+            (
+                [z[0], z[1], z[2], z[3], z[4]],
+                [z[0], z[1], z[5], z[6], z[7]],
+            )
+        })
+        .unzip();
+    for (name, results) in [
+        ("inter_min.csv", results_min),
+        ("inter_max.csv", results_max),
+    ] {
+        let mut tabelle = Tabelle::neu([
+            ("breite", "m"),
+            ("hoehe", "m"),
+            ("geschwindigkeit", "m/s"),
+            ("nachweis", ""),
+            ("verletzung", ""),
+        ]);
+        for f in results {
+            tabelle.zeile(f);
+        }
+        ausgabe.schreibe_tabelle(name, &tabelle);
+    }
+    fortschritt.beende();
+    sicherung.abschliessen();
+}
+
+/**
+Sucht von oben die größte Öffnungsgeschwindigkeit, bei der alle Nachweise eingehalten sind,
+und simuliert die Schleuse damit (siehe `simuliere_schleuse`).
+*/
+pub fn minimiere_geschwi(
+    schleuse: Schleusenwerte,
+    vgesch: (f64, f64),
+    breite: f64,
+    hoehe: f64,
+    nachweise: &Nachweise,
+    anzahl_schritte: u32,
+    ausgabe: &Ausgabe,
+) {
+    let mut v_momentan = vgesch.1;
+    let mut v_last = 0.0;
+    let schrittweite = 1.0 / anzahl_schritte as f64;
+    let fortschritt = Fortschritt::neu("Geschwindigkeit", anzahl_schritte as usize, 0);
+    for i in 0..anzahl_schritte {
+        // Hilfswerte als Double
+
+        let momentan_schritt = schrittweite * i as f64;
+        // Geschwindigkeitsauswahl, hierbei wird von der oberen Grenze ausgegangen
+
+        v_momentan = interpolate(vgesch, 1.0 - momentan_schritt);
+        info!("v_m = {}", v_momentan);
+        // Simulieren der Schleuse
+        let k = zwischenspeicher().kennwerte(&schleuse.mit_rechteck(hoehe, breite, v_momentan));
+        // Überprüfen der Randbedingungen
+        let verletzungen = nachweise.pruefe_kennwerte(&k);
+        fortschritt.schritt();
+        for v in &verletzungen {
+            info!("Schleuse abgelehnt: {}", v);
+        }
+        if verletzungen.is_empty() {
+            if v_momentan > v_last {
+                v_last = v_momentan;
+                continue;
+            } else {
+                break;
+            }
+        }
+    }
+    fortschritt.beende();
+    println!("v_max = {} m/s", v_momentan);
+    simuliere_schleuse(&schleuse.mit_rechteck(hoehe, breite, v_momentan), ausgabe)
+}
+
+/**
+Pareto-Front zwischen Füllzeit, größter Wasserspiegelneigung und Gesamtfläche der rechteckigen Füllquerschnitte.
+Höhe, Breite und Öffnungsgeschwindigkeit werden in je `stufen` gleichen Schritten einschließlich
+der Bereichsgrenzen variiert. Varianten, die einen der übergebenen Nachweise verletzen, werden verworfen;
+ohne Nachweise bleibt die Front ungefiltert.
+Die nicht dominierten Varianten werden nach der Füllzeit sortiert nach `pareto.csv` geschrieben
+(Höhe, Breite, Öffnungsgeschwindigkeit, Gesamtfläche, Füllzeit, Wasserspiegelneigung).
+*/
+pub fn pareto_front(
+    schleuse: Schleusenwerte,
+    vgesch: (f64, f64),
+    vhoehe: (f64, f64),
+    vbreite: (f64, f64),
+    stufen: u32,
+    nachweise: &Nachweise,
+    ausgabe: &Ausgabe,
+) {
+    let stufen = stufen.max(1);
+    let mut varianten = Vec::new();
+    for i in 0..=stufen {
+        for j in 0..=stufen {
+            for k in 0..=stufen {
+                varianten.push((i, j, k));
+            }
+        }
+    }
+    let anteil = |n: u32| n as f64 / stufen as f64;
+    let rechtecke = schleuse.anzahl_rechtecke() as f64;
+    let fortschritt = Fortschritt::neu("Pareto-Front", varianten.len(), 0);
+    let results: Vec<[f64; 6]> = varianten
+        .into_par_iter()
+        .filter_map(|(i, j, k)| {
+            let hoehe = interpolate(vhoehe, anteil(i));
+            let breite = interpolate(vbreite, anteil(j));
+            let geschwi = interpolate(vgesch, anteil(k));
+            let k = zwischenspeicher().kennwerte(&schleuse.mit_rechteck(hoehe, breite, geschwi));
+            fortschritt.schritt();
+            if !nachweise.pruefe_kennwerte(&k).is_empty() {
+                return None;
+            }
+            Some([
+                hoehe,
+                breite,
+                geschwi,
+                hoehe * breite * rechtecke,
+                k.fuellzeit,
+                k.wasserspiegelneigung,
+            ])
+        })
+        .collect();
+    fortschritt.beende();
+
+    let ziele: Vec<Vec<f64>> = results.iter().map(|r| vec![r[3], r[4], r[5]]).collect();
+    let mut front: Vec<[f64; 6]> = nicht_dominiert(&ziele)
+        .into_iter()
+        .map(|i| results[i])
+        .collect();
+    front.sort_by(|a, b| a[4].total_cmp(&b[4]).then(a[5].total_cmp(&b[5])));
+    println!(
+        "{} von {} zulässigen Varianten liegen auf der Pareto-Front",
+        front.len(),
+        results.len()
+    );
+    let mut tabelle = Tabelle::neu([
+        ("hoehe", "m"),
+        ("breite", "m"),
+        ("geschwindigkeit", "m/s"),
+        ("flaeche", "m²"),
+        ("fuellzeit", "s"),
+        ("wasserspiegelneigung", "mm/m"),
+    ]);
+    for f in front {
+        tabelle.zeile(f);
+    }
+    ausgabe.schreibe_tabelle("pareto.csv", &tabelle);
+}
+
+/**
+Empfindlichkeit von Füllzeit und größter Wasserspiegelneigung gegenüber allen Eingaben der Schleuse,
+einschließlich Querschnitten und Verlustbeiwerten (siehe `eingabeparameter`).
+Jede Eingabe wird um den Anteil `schritt` ihres Werts, bei Null um `schritt` absolut, nach oben
+und unten gestört und die Ableitung aus zentralen Differenzen gebildet. Der Einfluss ist die relative
+Änderung des Ergebnisses bei dieser Störung, nach dem größeren der beiden Einflüsse sind die Eingaben
+in `sensitivitaet.csv` sortiert (Pfad, Wert, Störung, dT/dx, dI/dx, Einfluss Füllzeit, Einfluss Neigung).
+Bei festem Zeitschritt wäre die Füllzeit auf den Zeitschritt gerundet und dT/dx meist 0 oder ein Sprung,
+daher wird dann mit `rk45` und dem ersten Zeitschritt der Eingabe gerechnet. Die Füllzeit wird zudem
+nicht als Zeit des letzten Schritts, sondern mit `interpolierte_fuellzeit` bestimmt.
+*/
+pub fn sensitivitaet(schleuse: Schleusenwerte, schritt: f64, ausgabe: &Ausgabe) {
+    let mut schleuse = mit_allen_verlusten(&schleuse);
+    if schleuse.zeitintegration.verfahren != Verfahren::DormandPrince {
+        println!("Rechnung mit rk45, bei festem Zeitschritt ist die Füllzeit auf den Zeitschritt gerundet");
+        schleuse.zeitintegration.verfahren = Verfahren::DormandPrince;
+    }
+    let (zeit0, neigung0) = kennwerte(&schleuse);
+    println!("T = {} s, I_w = {} mm/m", zeit0, neigung0);
+
+    let pfade = eingabeparameter(&schleuse);
+    let fortschritt = Fortschritt::neu("Sensitivität", pfade.len(), 0);
+    let mut ergebnisse: Vec<(String, [f64; 6])> = pfade
+        .into_par_iter()
+        .filter_map(|pfad| {
+            let wert = lese_parameter(&schleuse, &pfad).ok()?;
+            let stoerung = if wert == 0.0 {
+                schritt
+            } else {
+                schritt * wert.abs()
+            };
+            let gestoert = |neu: f64| match setze_parameter(&schleuse, &[(pfad.clone(), neu)]) {
+                Ok(werte) => Some(kennwerte(&werte)),
+                Err(why) => {
+                    error!("Couldn't vary {}: {}", pfad, why);
+                    None
+                }
+            };
+            let (zeit_plus, neigung_plus) = gestoert(wert + stoerung)?;
+            let (zeit_minus, neigung_minus) = gestoert(wert - stoerung)?;
+            fortschritt.schritt();
+            Some((
+                pfad,
+                [
+                    wert,
+                    stoerung,
+                    (zeit_plus - zeit_minus) / (2.0 * stoerung),
+                    (neigung_plus - neigung_minus) / (2.0 * stoerung),
+                    (zeit_plus - zeit_minus) / (2.0 * zeit0),
+                    (neigung_plus - neigung_minus) / (2.0 * neigung0),
+                ],
+            ))
+        })
+        .collect();
+    fortschritt.beende();
+    let einfluss = |w: &[f64; 6]| w[4].abs().max(w[5].abs());
+    ergebnisse.sort_by(|a, b| einfluss(&b.1).total_cmp(&einfluss(&a.1)));
+
+    for (pfad, w) in ergebnisse.iter().take(10) {
+        println!(
+            "{}: Einfluss Füllzeit {:+.4}, Wasserspiegelneigung {:+.4}",
+            pfad, w[4], w[5]
+        );
+    }
+    let mut tabelle = Tabelle::neu([
+        ("pfad", ""),
+        ("wert", ""),
+        ("stoerung", ""),
+        ("dT/dx", ""),
+        ("dI/dx", ""),
+        ("einfluss_fuellzeit", ""),
+        ("einfluss_neigung", ""),
+    ]);
+    for (pfad, w) in &ergebnisse {
+        tabelle.zeile(std::iter::once(Zelle::from(pfad.as_str())).chain(w.map(Zelle::from)));
+    }
+    ausgabe.schreibe_tabelle("sensitivitaet.csv", &tabelle);
+}
+
+/**
+Monte-Carlo-Simulation der Füllung mit den Unsicherheiten der Eingabedatei.
+Alle Stichproben werden vorab mit dem Startwert `startwert` gezogen, das Ergebnis hängt daher
+nicht von der Anzahl der Threads ab. Ausgegeben werden Mittelwert und `perzentile` von Füllzeit,
+größtem Durchfluss und größter Wasserspiegelneigung sowie, wenn Nachweise angegeben sind,
+die Wahrscheinlichkeit, sie zu verletzen. Jede Stichprobe steht in `montecarlo.csv`
+(gezogene Werte, Füllzeit, größter Durchfluss, Wasserspiegelneigung, 1 bei verletzten Nachweisen).
+*/
+pub fn monte_carlo(
+    schleuse: Schleusenwerte,
+    anzahl: u32,
+    startwert: u64,
+    perzentile: &[f64],
+    ausgabe: &Ausgabe,
+) {
+    let werte = mit_allen_verlusten(&schleuse);
+    let pfade = erweitere_pfade(&werte, werte.unsicherheiten.iter().map(|u| &u.pfad));
+    if pfade.is_empty() {
+        println!("Keine Unsicherheiten in der Eingabedatei angegeben");
+    }
+
+    let mut zufall = Zufall::neu(startwert);
+    let stichproben: Vec<Vec<f64>> = (0..anzahl)
+        .map(|_| {
+            werte
+                .unsicherheiten
+                .iter()
+                .map(|u| u.verteilung.ziehe(&mut zufall))
+                .collect()
+        })
+        .collect();
+
+    let fortschritt = Fortschritt::neu("Monte-Carlo", stichproben.len(), 0);
+    let results: Vec<Vec<f64>> = stichproben
+        .into_par_iter()
+        .map(|gezogen| {
+            let (kennwerte, erfuellt) = simuliere_variante(&werte, &pfade, &gezogen);
+            fortschritt.schritt();
+            let mut zeile = gezogen;
+            zeile.push(kennwerte.fuellzeit);
+            zeile.push(kennwerte.max_durchfluss);
+            zeile.push(kennwerte.wasserspiegelneigung);
+            zeile.push(if erfuellt { 0.0 } else { 1.0 });
+            zeile
+        })
+        .collect();
+    fortschritt.beende();
+
+    let spalte = results.first().map_or(0, |z| z.len() - 4);
+    for (k, name) in [
+        "Füllzeit in s",
+        "Durchfluss in m³/s",
+        "Wasserspiegelneigung in mm/m",
+    ]
+    .iter()
+    .enumerate()
+    {
+        let mut werte_k: Vec<f64> = results.iter().map(|z| z[spalte + k]).collect();
+        werte_k.sort_by(f64::total_cmp);
+        let mittel = werte_k.iter().sum::<f64>() / werte_k.len() as f64;
+        let p = perzentile
+            .iter()
+            .map(|&p| format!("P{} = {}", p, perzentil(&werte_k, p)))
+            .collect::<Vec<String>>()
+            .join(", ");
+        println!("{}: Mittel = {}, {}", name, mittel, p);
+    }
+    if !werte.nachweise.ist_leer() && !results.is_empty() {
+        let verletzt = results.iter().filter(|z| z[spalte + 3] > 0.0).count();
+        println!(
+            "Wahrscheinlichkeit verletzter Nachweise: {}",
+            verletzt as f64 / results.len() as f64
+        );
+    }
+    let mut tabelle = Tabelle::neu(
+        werte
+            .unsicherheiten
+            .iter()
+            .map(|u| (u.pfad.as_str(), ""))
+            .chain([
+                ("fuellzeit", "s"),
+                ("max_durchfluss", "m³/s"),
+                ("wasserspiegelneigung", "mm/m"),
+                ("verletzt", ""),
+            ]),
+    );
+    for z in results {
+        tabelle.zeile(z);
+    }
+    ausgabe.schreibe_tabelle("montecarlo.csv", &tabelle);
+}
+
+/**
+Wertet den Versuchsplan der Eingabedatei aus, die Ergebnisse stehen in `versuchsplan.csv`
+(siehe `werte_punkte_aus`).
+*/
+pub fn fuehre_versuchsplan_aus(schleuse: Schleusenwerte, ausgabe: &Ausgabe) {
+    let werte = mit_allen_verlusten(&schleuse);
+    let punkte = match werte.versuchsplan.punkte() {
+        Ok(punkte) => punkte,
+        Err(why) => panic!("Couldn't create design of experiments: {}", why),
+    };
+    let pfade: Vec<String> = werte
+        .versuchsplan
+        .faktoren
+        .iter()
+        .map(|f| f.pfad.clone())
+        .collect();
+    werte_punkte_aus(&werte, &pfade, punkte, ausgabe, "versuchsplan");
+}
+
+/**
+Rechnet alle Kombinationen der `[[variation]]`-Abschnitte der Eingabedatei durch,
+die Ergebnisse stehen in `variation.csv` (siehe `werte_punkte_aus`).
+*/
+pub fn variiere(schleuse: Schleusenwerte, ausgabe: &Ausgabe) {
+    let werte = mit_allen_verlusten(&schleuse);
+    let punkte = match kombinationen(&werte.variation) {
+        Ok(punkte) => punkte,
+        Err(why) => panic!("Couldn't create variation: {}", why),
+    };
+    let pfade: Vec<String> = werte.variation.iter().map(|v| v.pfad.clone()).collect();
+    werte_punkte_aus(&werte, &pfade, punkte, ausgabe, "variation");
+}
+
+/**
+Simuliert die Schleuse für jeden Punkt, dessen Werte in der Reihenfolge von `pfade` eingesetzt werden.
+Je Punkt steht in `<name>.csv` eine Zeile mit den Werten des Punkts, Füllzeit, größtem Durchfluss,
+größter Durchflusszunahme, Wasserspiegelneigung, Anzahl der Vollöffnungen und 1,
+wenn alle Nachweise eingehalten sind.
+*/
+fn werte_punkte_aus(
+    werte: &Schleusenwerte,
+    pfade: &[String],
+    punkte: Vec<Vec<f64>>,
+    ausgabe: &Ausgabe,
+    name: &str,
+) {
+    let erweitert = erweitere_pfade(werte, pfade.iter());
+    let sicherung = oeffne_sicherung(ausgabe, name, werte, format!("{:?}", pfade));
+    let fortschritt = Fortschritt::neu(name, punkte.len(), sicherung.anzahl());
+    println!("{} Punkte", punkte.len());
+
+    let results: Vec<Vec<f64>> = punkte
+        .into_par_iter()
+        .enumerate()
+        .map(|(index, punkt)| {
+            let ergebnis = sicherung.oder_berechne(index, || {
+                let (k, erfuellt) = simuliere_variante(werte, &erweitert, &punkt);
+                fortschritt.schritt();
+                [
+                    k.fuellzeit,
+                    k.max_durchfluss,
+                    k.max_durchflusszunahme,
+                    k.wasserspiegelneigung,
+                    k.fuelloeffnungen as f64,
+                    if erfuellt { 1.0 } else { 0.0 },
+                ]
+            });
+            let mut zeile = punkt;
+            zeile.extend(ergebnis);
+            zeile
+        })
+        .collect();
+    let mut tabelle = Tabelle::neu(pfade.iter().map(|p| (p.as_str(), "")).chain([
+        ("fuellzeit", "s"),
+        ("max_durchfluss", "m³/s"),
+        ("max_durchflusszunahme", "m³/s²"),
+        ("wasserspiegelneigung", "mm/m"),
+        ("fuelloeffnungen", ""),
+        ("erfuellt", ""),
+    ]));
+    for z in results {
+        tabelle.zeile(z);
+    }
+    ausgabe.schreibe_tabelle(&format!("{}.csv", name), &tabelle);
+    fortschritt.beende();
+    sicherung.abschliessen();
+}
+
+// Zielgröße der Optimierung des Füllquerschnitts
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Zielgroesse {
+    // Gesamtfläche der rechteckigen Füllquerschnitte, Höhe mal Breite mal Anzahl
+    Flaeche,
+    Fuellzeit,
+}
+
+/**
+Minimiert die Zielgröße über Höhe, Breite und Öffnungsgeschwindigkeit aller rechteckigen
+Füllquerschnitte innerhalb der Bereiche mit dem Verfahren nach Nelder und Mead.
+Die Nachweise gehen als Strafterm ein: die Zielgröße bezogen auf ihren Wert in der Mitte der
+Bereiche plus `strafe` mal die Summe der bezogenen Verletzungen.
+Das Optimum wird ausgegeben und wie bei `simuliere_schleuse` vollständig simuliert.
+*/
+#[allow(clippy::too_many_arguments)]
+pub fn optimiere_rechteck(
+    schleuse: Schleusenwerte,
+    vgesch: (f64, f64),
+    vhoehe: (f64, f64),
+    vbreite: (f64, f64),
+    ziel: Zielgroesse,
+    nachweise: &Nachweise,
+    strafe: f64,
+    verfahren: &NelderMead,
+    ausgabe: &Ausgabe,
+) {
+    let untere = [vhoehe.0, vbreite.0, vgesch.0];
+    let obere = [vhoehe.1, vbreite.1, vgesch.1];
+    let rechtecke = schleuse.anzahl_rechtecke() as f64;
+    let zielwert = |x: &[f64]| -> (f64, Vec<Verletzung>) {
+        let k = zwischenspeicher().kennwerte(&schleuse.mit_rechteck(x[0], x[1], x[2]));
+        let wert = match ziel {
+            Zielgroesse::Flaeche => x[0] * x[1] * rechtecke,
+            Zielgroesse::Fuellzeit => k.fuellzeit,
+        };
+        (wert, nachweise.pruefe_kennwerte(&k))
+    };
+
+    let start: Vec<f64> = untere
+        .iter()
+        .zip(&obere)
+        .map(|(u, o)| 0.5 * (u + o))
+        .collect();
+    let bezug = match zielwert(&start).0 {
+        b if b.is_finite() && b > 0.0 => b,
+        _ => 1.0,
+    };
+    let optimum = verfahren.minimiere(&untere, &obere, &start, |x| {
+        let (wert, verletzungen) = zielwert(x);
+        wert / bezug + strafe * verletzungen.iter().map(|v| v.anteil()).sum::<f64>()
+    });
+
+    let [hoehe, breite, geschwi] = [optimum.punkt[0], optimum.punkt[1], optimum.punkt[2]];
+    let (wert, verletzungen) = zielwert(&optimum.punkt);
+    println!(
+        "Optimum nach {} Simulationen: h = {} m, b = {} m, v = {} m/s, {:?} = {}",
+        optimum.auswertungen, hoehe, breite, geschwi, ziel, wert
+    );
+    if verletzungen.is_empty() {
+        println!("Alle Nachweise erfüllt");
+    }
+    for v in &verletzungen {
+        println!("Nicht erfüllt: {}", v);
+    }
+    simuliere_schleuse(&schleuse.mit_rechteck(hoehe, breite, geschwi), ausgabe)
+}
+
+/**
+Passt Ersatzmodelle an die Ergebnistabellen einer Studie an, etwa `versuchsplan.csv` oder `dimenXXX.csv`.
+`eingaenge` und `ausgaenge` sind die Spalten (ab 0) der Tabellen, Zeilen mit ungültigen Werten werden übergangen.
+Modelle und Eingänge tragen die Namen aus der Kopfzeile der ersten Tabelle, ohne Kopfzeile `spalte N`.
+Die Güte wird ausgegeben, die Modelle werden in `ersatzmodell.toml` gespeichert (siehe `ersatzmodell_abfragen`).
+*/
+pub fn ersatzmodell_anpassen(
+    tabellen: &[&Path],
+    eingaenge: &[usize],
+    ausgaenge: &[usize],
+    art: Modellart,
+    grad: u32,
+    ausgabe: &Ausgabe,
+) {
+    let gelesen: Vec<Zahlentabelle> = tabellen
+        .iter()
+        .map(|t| lies_zahlentabelle(t).unwrap_or_else(|why| panic!("{}", why)))
+        .collect();
+    // Spaltennamen aus der ersten Kopfzeile, ohne Kopfzeile die Nummer der Spalte
+    let kopf = gelesen
+        .iter()
+        .map(|t| &t.kopf)
+        .find(|k| !k.is_empty())
+        .cloned();
+    let name = |spalte: usize| match kopf.as_ref().and_then(|k| k.get(spalte)) {
+        Some(name) => name.clone(),
+        None => format!("spalte {}", spalte),
+    };
+    let zeilen: Vec<Vec<f64>> = gelesen
+        .into_iter()
+        .flat_map(|t| t.zeilen)
+        .filter(|z| {
+            eingaenge
+                .iter()
+                .chain(ausgaenge)
+                .all(|&s| z.get(s).is_some_and(|w| w.is_finite()))
+        })
+        .collect();
+    let mut zeilen: Vec<(Vec<f64>, Vec<f64>)> = zeilen
+        .into_iter()
+        .map(|z| (eingaenge.iter().map(|&s| z[s]).collect(), z))
+        .collect();
+    // Doppelte Stützstellen machen das Gleichungssystem des RBF-Modells singulär, die erste bleibt
+    let anzahl = zeilen.len();
+    zeilen.sort_by(|(a, _), (b, _)| {
+        a.iter()
+            .zip(b)
+            .map(|(a, b)| a.total_cmp(b))
+            .find(|o| o.is_ne())
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    zeilen.dedup_by(|(a, _), (b, _)| a == b);
+    if zeilen.len() < anzahl {
+        let text = format!("{} duplicate data point(s) dropped", anzahl - zeilen.len());
+        warn!("{}", text);
+        eprintln!("Warning: {}", text);
+    }
+    let (x, zeilen): (Vec<Vec<f64>>, Vec<Vec<f64>>) = zeilen.into_iter().unzip();
+    println!("{} Stützstellen", x.len());
+
+    let mut modelle = Vec::new();
+    for &spalte in ausgaenge {
+        let y: Vec<f64> = zeilen.iter().map(|z| z[spalte]).collect();
+        let name = name(spalte);
+        let modell = match Ersatzmodell::anpassen(&name, art, grad, &x, &y) {
+            Ok(modell) => modell,
+            Err(why) => panic!("Couldn't fit surrogate for {}: {}", name, why),
+        };
+        // Das Modell selbst ist brauchbar, auch wenn ein Teilmodell der Kreuzvalidierung scheitert
+        match guete(&modell, &x, &y, 10) {
+            Ok(g) => println!(
+                "{}: R² = {:.5}, RMSE = {}, RMSE 10-fache Kreuzvalidierung = {}",
+                name, g.bestimmtheitsmass, g.rmse, g.kreuzvalidierung
+            ),
+            Err(why) => {
+                let text = format!("Couldn't cross-validate surrogate for {}: {}", name, why);
+                warn!("{}", text);
+                eprintln!("Warning: {}", text);
+            }
+        }
+        modelle.push(modell);
+    }
+
+    let modelle = Ersatzmodelle {
+        eingaenge: eingaenge.iter().map(|&s| name(s)).collect(),
+        modelle,
+    };
+    match toml::to_string(&modelle) {
+        Ok(r) => ausgabe.schreibe_text("ersatzmodell.toml", &r),
+        Err(why) => error!("Couldn't serialize surrogate: {}", why),
+    }
+}
+
+/**
+Wertet die gespeicherten Ersatzmodelle am Punkt `punkt` aus, ohne zu simulieren.
+*/
+pub fn ersatzmodell_abfragen(datei: &Path, punkt: &[f64]) {
+    let text = match std::fs::read_to_string(datei) {
+        Ok(text) => text,
+        Err(why) => panic!("Couldn't read {}: {}", datei.display(), why),
+    };
+    let modelle: Ersatzmodelle = match toml::from_str(&text) {
+        Ok(modelle) => modelle,
+        Err(why) => panic!("Couldn't parse {}: {}", datei.display(), why),
+    };
+    if punkt.len() != modelle.eingaenge.len() {
+        panic!(
+            "Need {} values for {}",
+            modelle.eingaenge.len(),
+            modelle.eingaenge.join(", ")
+        )
+    }
+    for modell in &modelle.modelle {
+        if !modell.im_bereich(punkt) {
+            println!("Achtung: {} wird extrapoliert", modell.name);
+        }
+        println!("{} = {}", modell.name, modell.auswerten(punkt));
+    }
+}
+
+/**
+Sicherung `<name>.sicherung` einer Studie im Ausgabeverzeichnis. Die Kennung besteht aus dem
+Fingerabdruck der Eingabe und den übrigen Einstellungen der Studie.
+*/
+fn oeffne_sicherung(
+    ausgabe: &Ausgabe,
+    name: &str,
+    schleuse: &Schleusenwerte,
+    einstellungen: String,
+) -> Sicherung {
+    let sicherung = Sicherung::oeffne(
+        &ausgabe.datei(&format!("{}.sicherung", name)),
+        &format!(
+            "{} {:016x} {}",
+            name,
+            schleuse.fingerabdruck(),
+            einstellungen
+        ),
+    );
+    if sicherung.anzahl() > 0 {
+        println!(
+            "Fortsetzung mit {} fertigen Ergebnissen aus {}.sicherung",
+            sicherung.anzahl(),
+            name
+        );
+    }
+    sicherung
+}
+
+// Pfade mit `[*]` für alle Listeneinträge ausschreiben, ein Eintrag je variiertem Wert
+fn erweitere_pfade<'a>(
+    werte: &Schleusenwerte,
+    pfade: impl Iterator<Item = &'a String>,
+) -> Vec<Vec<String>> {
+    pfade
+        .map(|pfad| match erweitere_pfad(werte, pfad) {
+            Ok(pfade) => pfade,
+            Err(why) => panic!("Couldn't vary {}: {}", pfad, why),
+        })
+        .collect()
+}
+
+// Füllung mit den angegebenen Werten an den Pfaden, dazu ob alle Nachweise eingehalten sind
+fn simuliere_variante(
+    werte: &Schleusenwerte,
+    pfade: &[Vec<String>],
+    neu: &[f64],
+) -> (Kennwerte, bool) {
+    let mut aenderungen = Vec::new();
+    for (p, wert) in pfade.iter().zip(neu) {
+        aenderungen.extend(p.iter().map(|pfad| (pfad.clone(), *wert)));
+    }
+    let variante = match setze_parameter(werte, &aenderungen) {
+        Ok(variante) => variante,
+        Err(why) => panic!("Couldn't apply {:?}: {}", neu, why),
+    };
+    let k = zwischenspeicher().kennwerte(&variante);
+    (k, werte.nachweise.pruefe_kennwerte(&k).is_empty())
+}
+
+// Interpolierte Füllzeit und größte Wasserspiegelneigung einer Eingabe, ohne Zwischenspeicher
+fn kennwerte(werte: &Schleusenwerte) -> (f64, f64) {
+    let shl = werte.erschaffe_schleuse();
+    let res = shl.fuell_schleuse();
+    (
+        interpolierte_fuellzeit(&shl, &res),
+        wasserspiegelneigung(&shl, &res),
+    )
+}
+
+// Alle Indexpaare eines Rasters mit Schrittweite 2, zeilenweise wie in den Ausgabedateien
+fn raster(zeilen: std::ops::Range<u32>, spalten: std::ops::Range<u32>) -> Vec<(u32, u32)> {
+    let mut paare = Vec::new();
+    for i in zeilen.step_by(2) {
+        for j in spalten.clone().step_by(2) {
+            paare.push((i, j));
+        }
+    }
+    paare
+}
+
+fn interpolate(bet: (f64, f64), t: f64) -> f64 {
+    bet.0 + (bet.1 - bet.0) * t
+}
@@ -0,0 +1,116 @@
+/**
+Einstellungen der Suche nach dem ersten zulässigen Wert in einem Bereich.
+Der Bereich wird zuerst in `stufen` gleich große Abschnitte geteilt, um den ersten zulässigen
+Abschnitt einzugrenzen. Darin wird halbiert, bis die Grenze auf `toleranz` genau bekannt ist.
+Die Toleranz ist ein Anteil an der Breite des Bereichs, höchstens `MAX_HALBIERUNGEN`-mal wird halbiert.
+Ein zulässiges Fenster, das schmaler als eine Stufe ist, kann zwischen zwei Stufen liegen und wird
+dann nicht gefunden; für solche Fälle `stufen` erhöhen.
+*/
+#[derive(Clone, Copy, Debug)]
+pub struct Suche {
+    pub stufen: u32,
+    pub toleranz: f64,
+}
+
+// Danach ist die Grenze auf die Rechengenauigkeit bekannt, auch bei Toleranz 0
+const MAX_HALBIERUNGEN: u32 = 60;
+
+impl Default for Suche {
+    fn default() -> Self {
+        Suche {
+            stufen: 10,
+            toleranz: 1e-3,
+        }
+    }
+}
+
+impl Suche {
+    /**
+    Sucht von `von` in Richtung `bis` den ersten Wert, für den `zulaessig` gilt.
+    `von` darf größer als `bis` sein, dann wird der größte zulässige Wert gesucht.
+    Zurückgegeben wird der zulässige Rand der eingegrenzten Grenze oder `None`,
+    wenn keine der Stufen zulässig ist.
+    */
+    pub fn erster_zulaessiger(
+        &self,
+        von: f64,
+        bis: f64,
+        zulaessig: impl Fn(f64) -> bool,
+    ) -> Option<f64> {
+        if zulaessig(von) {
+            return Some(von);
+        }
+        let stufen = self.stufen.max(1);
+        let mut unzulaessig = von;
+        let mut gefunden = None;
+        for k in 1..=stufen {
+            let wert = von + (bis - von) * k as f64 / stufen as f64;
+            if zulaessig(wert) {
+                gefunden = Some(wert);
+                break;
+            }
+            unzulaessig = wert;
+        }
+        let mut gefunden = gefunden?;
+
+        // Halbieren zwischen dem letzten unzulässigen und dem ersten zulässigen Wert
+        let genauigkeit = self.toleranz * (bis - von).abs();
+        for _ in 0..MAX_HALBIERUNGEN {
+            if (gefunden - unzulaessig).abs() <= genauigkeit {
+                break;
+            }
+            let mitte = (gefunden + unzulaessig) / 2.0;
+            if zulaessig(mitte) {
+                gefunden = mitte;
+            } else {
+                unzulaessig = mitte;
+            }
+        }
+        Some(gefunden)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grenze_wird_auf_die_toleranz_genau_gefunden() {
+        let suche = Suche::default();
+        let wert = suche.erster_zulaessiger(0.0, 1.0, |x| x >= 0.37).unwrap();
+        assert!((0.37..=0.371).contains(&wert));
+        // Rückwärts wird der größte zulässige Wert gesucht
+        let wert = suche.erster_zulaessiger(1.0, 0.0, |x| x <= 0.37).unwrap();
+        assert!((0.369..=0.37).contains(&wert));
+    }
+
+    #[test]
+    fn schmales_fenster_braucht_genug_stufen() {
+        let fenster = |x: f64| (0.42..=0.47).contains(&x);
+        assert_eq!(Suche::default().erster_zulaessiger(0.0, 1.0, fenster), None);
+        let suche = Suche {
+            stufen: 40,
+            ..Suche::default()
+        };
+        let wert = suche.erster_zulaessiger(0.0, 1.0, fenster).unwrap();
+        assert!((0.42..=0.421).contains(&wert));
+    }
+
+    #[test]
+    fn leerer_bereich() {
+        let suche = Suche::default();
+        assert_eq!(suche.erster_zulaessiger(0.5, 0.5, |x| x > 0.5), None);
+        assert_eq!(suche.erster_zulaessiger(0.5, 0.5, |x| x >= 0.5), Some(0.5));
+        assert_eq!(suche.erster_zulaessiger(0.0, 1.0, |_| false), None);
+    }
+
+    #[test]
+    fn grenze_am_rand_des_bereichs() {
+        let suche = Suche::default();
+        assert_eq!(suche.erster_zulaessiger(0.0, 1.0, |x| x >= 0.0), Some(0.0));
+        let wert = suche.erster_zulaessiger(0.0, 1.0, |x| x >= 1.0).unwrap();
+        assert_eq!(wert, 1.0);
+        let wert = suche.erster_zulaessiger(0.0, 1.0, |x| x >= 0.9995).unwrap();
+        assert!((0.9995..=1.0).contains(&wert));
+    }
+}
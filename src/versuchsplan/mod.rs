@@ -0,0 +1,305 @@
+use serde::{Deserialize, Serialize};
+
+use crate::zufall::Zufall;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum Planverfahren {
+    // Alle Kombinationen von `stufen` gleichmäßig verteilten Stufen je Faktor
+    Vollfaktoriell,
+    // Latin Hypercube: jeder Faktor trifft jede seiner `anzahl` Schichten genau einmal
+    Lhs,
+    // Quasi-Zufallsfolge nach Sobol mit Richtungszahlen nach Joe und Kuo
+    Sobol,
+}
+
+// Eingabe, die zwischen `min` und `max` variiert wird, Pfad wie in `parameter::lese_parameter`
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Faktor {
+    pub pfad: String,
+    pub min: f64,
+    pub max: f64,
+}
+
+/**
+Versuchsplan über beliebige Zahlenwerte der Eingabe:
+
+```toml
+[versuchsplan]
+verfahren = "lhs" # "vollfaktoriell", "lhs" oder "sobol"
+anzahl = 200      # Punkte bei "lhs" und "sobol"
+stufen = 5        # Stufen je Faktor bei "vollfaktoriell"
+startwert = 1     # Zufallszahlen bei "lhs"
+
+[[versuchsplan.faktoren]]
+pfad = "fuellsystem[*].querschnitt.hoehe"
+min = 0.25
+max = 0.35
+```
+*/
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct Versuchsplan {
+    pub verfahren: Planverfahren,
+    pub anzahl: usize,
+    pub stufen: usize,
+    pub startwert: u64,
+    pub faktoren: Vec<Faktor>,
+}
+
+impl Default for Versuchsplan {
+    fn default() -> Self {
+        Versuchsplan {
+            verfahren: Planverfahren::Lhs,
+            anzahl: 100,
+            stufen: 3,
+            startwert: 1,
+            faktoren: Vec::new(),
+        }
+    }
+}
+
+impl Versuchsplan {
+    /**
+    Punkte des Plans, je Punkt ein Wert für jeden Faktor in der Reihenfolge der Faktoren.
+    */
+    pub fn punkte(&self) -> Result<Vec<Vec<f64>>, String> {
+        let einheitswuerfel = match self.verfahren {
+            Planverfahren::Vollfaktoriell => vollfaktoriell(self.faktoren.len(), self.stufen),
+            Planverfahren::Lhs => latin_hypercube(self.faktoren.len(), self.anzahl, self.startwert),
+            Planverfahren::Sobol => sobol(self.faktoren.len(), self.anzahl)?,
+        };
+        Ok(einheitswuerfel
+            .into_iter()
+            .map(|punkt| {
+                punkt
+                    .iter()
+                    .zip(&self.faktoren)
+                    .map(|(u, f)| f.min + u * (f.max - f.min))
+                    .collect()
+            })
+            .collect())
+    }
+}
+
+/**
+Variation einer Eingabe über ihren Pfad, entweder als Liste oder als Bereich mit gleichen Schritten:
+
+```toml
+[[variation]]
+pfad = "kammerlaenge"
+von = 180.0
+bis = 200.0
+schritte = 5
+
+[[variation]]
+pfad = "fuellsystem[2].startzeit"
+werte = [0.0, 30.0, 60.0]
+```
+
+Mehrere Variationen werden vollständig miteinander kombiniert, siehe `kombinationen`.
+*/
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Variation {
+    pub pfad: String,
+    #[serde(default)]
+    pub werte: Vec<f64>,
+    pub von: Option<f64>,
+    pub bis: Option<f64>,
+    pub schritte: Option<usize>,
+}
+
+impl Variation {
+    // Die Werte der Liste oder des Bereichs, `von` und `bis` eingeschlossen
+    pub fn werte(&self) -> Result<Vec<f64>, String> {
+        match (self.werte.is_empty(), self.von, self.bis, self.schritte) {
+            (false, None, None, None) => Ok(self.werte.clone()),
+            (true, Some(von), Some(bis), Some(schritte)) if schritte > 1 => Ok((0..schritte)
+                .map(|k| von + (bis - von) * k as f64 / (schritte - 1) as f64)
+                .collect()),
+            _ => Err(format!(
+                "{}: need either werte or von, bis and schritte (at least 2)",
+                self.pfad
+            )),
+        }
+    }
+}
+
+/**
+Alle Kombinationen der Werte der Variationen, die letzte Variation ändert sich am schnellsten.
+*/
+pub fn kombinationen(variationen: &[Variation]) -> Result<Vec<Vec<f64>>, String> {
+    let mut punkte = vec![Vec::new()];
+    for variation in variationen {
+        let werte = variation.werte()?;
+        punkte = punkte
+            .into_iter()
+            .flat_map(|p| {
+                werte.iter().map(move |w| {
+                    let mut p = p.clone();
+                    p.push(*w);
+                    p
+                })
+            })
+            .collect();
+    }
+    Ok(punkte)
+}
+
+fn vollfaktoriell(dimension: usize, stufen: usize) -> Vec<Vec<f64>> {
+    let stufe = |k: usize| {
+        if stufen > 1 {
+            k as f64 / (stufen - 1) as f64
+        } else {
+            0.5
+        }
+    };
+    let mut punkte = vec![Vec::new()];
+    for _ in 0..dimension {
+        punkte = punkte
+            .into_iter()
+            .flat_map(|p| {
+                (0..stufen.max(1)).map(move |k| {
+                    let mut p = p.clone();
+                    p.push(stufe(k));
+                    p
+                })
+            })
+            .collect();
+    }
+    punkte
+}
+
+fn latin_hypercube(dimension: usize, anzahl: usize, startwert: u64) -> Vec<Vec<f64>> {
+    let mut zufall = Zufall::neu(startwert);
+    let mut punkte = vec![Vec::with_capacity(dimension); anzahl];
+    for _ in 0..dimension {
+        // Zufällige Reihenfolge der Schichten nach Fisher-Yates
+        let mut schichten: Vec<usize> = (0..anzahl).collect();
+        for i in (1..anzahl).rev() {
+            let j = (zufall.naechste() % (i as u64 + 1)) as usize;
+            schichten.swap(i, j);
+        }
+        for (punkt, schicht) in punkte.iter_mut().zip(schichten) {
+            punkt.push((schicht as f64 + zufall.gleichverteilt()) / anzahl as f64);
+        }
+    }
+    punkte
+}
+
+// Grad, Koeffizienten und Startwerte der Richtungszahlen für die Dimensionen 2 bis 21 (Joe und Kuo, new-joe-kuo-6.21201)
+const SOBOL_RICHTUNGEN: [(u32, u32, &[u32]); 20] = [
+    (1, 0, &[1]),
+    (2, 1, &[1, 3]),
+    (3, 1, &[1, 3, 1]),
+    (3, 2, &[1, 1, 1]),
+    (4, 1, &[1, 1, 3, 3]),
+    (4, 4, &[1, 3, 5, 13]),
+    (5, 2, &[1, 1, 5, 5, 17]),
+    (5, 4, &[1, 1, 5, 5, 5]),
+    (5, 7, &[1, 1, 7, 11, 19]),
+    (5, 11, &[1, 1, 5, 1, 1]),
+    (5, 13, &[1, 1, 1, 3, 11]),
+    (5, 14, &[1, 3, 5, 5, 31]),
+    (6, 1, &[1, 3, 3, 9, 7, 49]),
+    (6, 13, &[1, 1, 1, 15, 21, 21]),
+    (6, 16, &[1, 3, 1, 13, 27, 49]),
+    (6, 19, &[1, 1, 1, 15, 7, 5]),
+    (6, 22, &[1, 3, 1, 15, 13, 25]),
+    (6, 25, &[1, 1, 5, 5, 19, 61]),
+    (7, 1, &[1, 3, 7, 11, 23, 15, 103]),
+    (7, 4, &[1, 3, 7, 13, 13, 15, 69]),
+];
+const SOBOL_BITS: usize = 32;
+
+fn richtungszahlen(dimension: usize) -> [u32; SOBOL_BITS] {
+    let mut v = [0u32; SOBOL_BITS];
+    if dimension == 0 {
+        for (i, vi) in v.iter_mut().enumerate() {
+            *vi = 1 << (SOBOL_BITS - 1 - i);
+        }
+        return v;
+    }
+    let (s, a, m) = SOBOL_RICHTUNGEN[dimension - 1];
+    let s = s as usize;
+    for i in 0..SOBOL_BITS {
+        v[i] = if i < s {
+            m[i] << (SOBOL_BITS - 1 - i)
+        } else {
+            let mut vi = v[i - s] ^ (v[i - s] >> s);
+            for k in 1..s {
+                if (a >> (s - 1 - k)) & 1 == 1 {
+                    vi ^= v[i - k];
+                }
+            }
+            vi
+        };
+    }
+    v
+}
+
+/**
+Die ersten `anzahl` Punkte der Sobol-Folge ohne den Nullpunkt, Gray-Code-Verfahren nach Antonov und Saleev.
+*/
+fn sobol(dimension: usize, anzahl: usize) -> Result<Vec<Vec<f64>>, String> {
+    if dimension > SOBOL_RICHTUNGEN.len() + 1 {
+        return Err(format!(
+            "Sobol-Folge nur bis {} Faktoren verfügbar",
+            SOBOL_RICHTUNGEN.len() + 1
+        ));
+    }
+    let richtungen: Vec<[u32; SOBOL_BITS]> = (0..dimension).map(richtungszahlen).collect();
+    let mut x = vec![0u32; dimension];
+    let mut punkte = Vec::with_capacity(anzahl);
+    for n in 0..anzahl {
+        // Index der niedrigsten Null im Binärcode von n
+        let c = (!n).trailing_zeros() as usize;
+        for (xj, v) in x.iter_mut().zip(&richtungen) {
+            *xj ^= v[c];
+        }
+        punkte.push(
+            x.iter()
+                .map(|&xj| xj as f64 / (1u64 << SOBOL_BITS) as f64)
+                .collect(),
+        );
+    }
+    Ok(punkte)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sobol_erste_punkte() {
+        let punkte = sobol(2, 7).unwrap();
+        let erwartet = [
+            [0.5, 0.5],
+            [0.75, 0.25],
+            [0.25, 0.75],
+            [0.375, 0.375],
+            [0.875, 0.875],
+            [0.625, 0.125],
+            [0.125, 0.625],
+        ];
+        for (punkt, erwartet) in punkte.iter().zip(erwartet) {
+            assert_eq!(punkt.as_slice(), erwartet.as_slice());
+        }
+    }
+
+    #[test]
+    fn latin_hypercube_ein_punkt_je_schicht() {
+        let anzahl = 17;
+        let punkte = latin_hypercube(3, anzahl, 42);
+        assert_eq!(punkte.len(), anzahl);
+        for i in 0..3 {
+            let mut schichten: Vec<usize> = punkte
+                .iter()
+                .map(|p| (p[i] * anzahl as f64) as usize)
+                .collect();
+            schichten.sort();
+            assert_eq!(schichten, (0..anzahl).collect::<Vec<_>>());
+        }
+    }
+}
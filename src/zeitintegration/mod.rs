@@ -0,0 +1,254 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum Verfahren {
+    // Explizites Euler-Verfahren mit festem Zeitschritt
+    #[serde(rename = "euler")]
+    Euler,
+    // Klassisches Runge-Kutta-Verfahren 4. Ordnung mit festem Zeitschritt
+    #[serde(rename = "rk4")]
+    RungeKutta4,
+    // Eingebettetes Runge-Kutta-Verfahren 5(4) nach Dormand-Prince mit Schrittweitensteuerung
+    #[serde(rename = "rk45")]
+    DormandPrince,
+}
+
+/**
+Einstellungen der Zeitintegration für die Kammerbilanz.
+
+```toml
+[zeitintegration]
+verfahren = "rk45"
+zeitschritt = 1.0 # Fester bzw. erster Zeitschritt in s
+toleranz = 1e-6   # Zulässiger lokaler Fehler des Kammerwasserspiegels in m
+```
+*/
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct Zeitintegration {
+    pub verfahren: Verfahren,
+    pub zeitschritt: f64,
+    pub toleranz: f64,
+    pub min_zeitschritt: f64,
+    pub max_zeitschritt: f64,
+    pub max_iterationen: u32,
+}
+
+impl Default for Zeitintegration {
+    fn default() -> Self {
+        Zeitintegration {
+            verfahren: Verfahren::Euler,
+            zeitschritt: 1.0,
+            toleranz: 1e-6,
+            min_zeitschritt: 1e-3,
+            max_zeitschritt: 10.0,
+            max_iterationen: 20000,
+        }
+    }
+}
+
+// Koeffizienten nach Dormand und Prince (1980)
+const DP_C: [f64; 7] = [0.0, 1.0 / 5.0, 3.0 / 10.0, 4.0 / 5.0, 8.0 / 9.0, 1.0, 1.0];
+const DP_A: [[f64; 6]; 7] = [
+    [0.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+    [1.0 / 5.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+    [3.0 / 40.0, 9.0 / 40.0, 0.0, 0.0, 0.0, 0.0],
+    [44.0 / 45.0, -56.0 / 15.0, 32.0 / 9.0, 0.0, 0.0, 0.0],
+    [
+        19372.0 / 6561.0,
+        -25360.0 / 2187.0,
+        64448.0 / 6561.0,
+        -212.0 / 729.0,
+        0.0,
+        0.0,
+    ],
+    [
+        9017.0 / 3168.0,
+        -355.0 / 33.0,
+        46732.0 / 5247.0,
+        49.0 / 176.0,
+        -5103.0 / 18656.0,
+        0.0,
+    ],
+    [
+        35.0 / 384.0,
+        0.0,
+        500.0 / 1113.0,
+        125.0 / 192.0,
+        -2187.0 / 6784.0,
+        11.0 / 84.0,
+    ],
+];
+// Gewichte der Lösung 5. Ordnung (= letzte Zeile von DP_A) und der eingebetteten 4. Ordnung
+const DP_B5: [f64; 7] = [
+    35.0 / 384.0,
+    0.0,
+    500.0 / 1113.0,
+    125.0 / 192.0,
+    -2187.0 / 6784.0,
+    11.0 / 84.0,
+    0.0,
+];
+const DP_B4: [f64; 7] = [
+    5179.0 / 57600.0,
+    0.0,
+    7571.0 / 16695.0,
+    393.0 / 640.0,
+    -92097.0 / 339200.0,
+    187.0 / 2100.0,
+    1.0 / 40.0,
+];
+
+impl Zeitintegration {
+    /**
+    Führt einen Zeitschritt der Differentialgleichung y' = f(t, y) ab (zeit, wert) aus.
+    Zurückgegeben werden die tatsächlich verwendete Schrittweite, der neue Wert
+    und die Schrittweite, mit der der nächste Schritt versucht werden soll.
+    */
+    pub fn schritt(
+        &self,
+        f: impl Fn(f64, f64) -> f64,
+        zeit: f64,
+        wert: f64,
+        schrittweite: f64,
+    ) -> (f64, f64, f64) {
+        match self.verfahren {
+            Verfahren::Euler => {
+                let neu = wert + schrittweite * f(zeit, wert);
+                (schrittweite, neu, schrittweite)
+            }
+            Verfahren::RungeKutta4 => {
+                let h = schrittweite;
+                let k1 = f(zeit, wert);
+                let k2 = f(zeit + h / 2.0, wert + h / 2.0 * k1);
+                let k3 = f(zeit + h / 2.0, wert + h / 2.0 * k2);
+                let k4 = f(zeit + h, wert + h * k3);
+                let neu = wert + h / 6.0 * (k1 + 2.0 * k2 + 2.0 * k3 + k4);
+                (h, neu, h)
+            }
+            Verfahren::DormandPrince => {
+                let mut h = schrittweite.clamp(self.min_zeitschritt, self.max_zeitschritt);
+                loop {
+                    let mut k = [0.0; 7];
+                    for i in 0..7 {
+                        let mut y = wert;
+                        for j in 0..i {
+                            y += h * DP_A[i][j] * k[j];
+                        }
+                        k[i] = f(zeit + DP_C[i] * h, y);
+                    }
+                    let mut neu = wert;
+                    let mut fehler = 0.0;
+                    for i in 0..7 {
+                        neu += h * DP_B5[i] * k[i];
+                        fehler += h * (DP_B5[i] - DP_B4[i]) * k[i];
+                    }
+                    let fehler = fehler.abs();
+
+                    let faktor = if fehler > 0.0 {
+                        (0.9 * (self.toleranz / fehler).powf(0.2)).clamp(0.2, 5.0)
+                    } else {
+                        5.0
+                    };
+                    let naechster = (h * faktor).clamp(self.min_zeitschritt, self.max_zeitschritt);
+                    if fehler <= self.toleranz || h <= self.min_zeitschritt {
+                        return (h, neu, naechster);
+                    }
+                    h = naechster;
+                }
+            }
+        }
+    }
+
+    // Restabstand zum Zielwasserspiegel, ab dem die Simulation als beendet gilt
+    pub fn endabstand(&self) -> f64 {
+        match self.verfahren {
+            Verfahren::DormandPrince => self.toleranz,
+            _ => 0.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // y' = -2 t y mit y(0) = 1 hat die Lösung y = exp(-t²)
+    fn f(t: f64, y: f64) -> f64 {
+        -2.0 * t * y
+    }
+
+    fn exakt(t: f64) -> f64 {
+        (-t * t).exp()
+    }
+
+    fn mit_verfahren(verfahren: Verfahren) -> Zeitintegration {
+        Zeitintegration {
+            verfahren,
+            // Ohne Fehlerschranke nimmt Dormand-Prince jeden Schritt an
+            toleranz: f64::INFINITY,
+            min_zeitschritt: 1e-6,
+            ..Zeitintegration::default()
+        }
+    }
+
+    // Fehler bei t = 1 mit `schritte` festen Schritten
+    fn fehler(integration: &Zeitintegration, schritte: u32) -> f64 {
+        let h = 1.0 / schritte as f64;
+        let mut wert = 1.0;
+        for i in 0..schritte {
+            let (verwendet, neu, _) = integration.schritt(f, i as f64 * h, wert, h);
+            assert_eq!(verwendet, h);
+            wert = neu;
+        }
+        (wert - exakt(1.0)).abs()
+    }
+
+    #[test]
+    fn konvergenzordnung() {
+        for (verfahren, ordnung, schritte) in [
+            (Verfahren::Euler, 1.0, 200),
+            (Verfahren::RungeKutta4, 4.0, 20),
+            (Verfahren::DormandPrince, 5.0, 20),
+        ] {
+            let integration = mit_verfahren(verfahren);
+            let grob = fehler(&integration, schritte);
+            let fein = fehler(&integration, 2 * schritte);
+            let gemessen = (grob / fein).log2();
+            assert!(
+                (gemessen - ordnung).abs() < 0.3,
+                "{:?}: Ordnung {} statt {}",
+                verfahren,
+                gemessen,
+                ordnung
+            );
+        }
+    }
+
+    #[test]
+    fn schrittweitensteuerung_haelt_toleranz_ein() {
+        let integration = Zeitintegration {
+            verfahren: Verfahren::DormandPrince,
+            toleranz: 1e-9,
+            ..Zeitintegration::default()
+        };
+        let (h, neu, naechster) = integration.schritt(f, 0.5, exakt(0.5), 1.0);
+        assert!(h < 1.0);
+        assert!((neu - exakt(0.5 + h)).abs() < 1e-9);
+        assert!(naechster >= integration.min_zeitschritt);
+    }
+
+    #[test]
+    fn kleinster_zeitschritt_wird_angenommen() {
+        let integration = Zeitintegration {
+            verfahren: Verfahren::DormandPrince,
+            toleranz: 1e-30,
+            min_zeitschritt: 0.01,
+            ..Zeitintegration::default()
+        };
+        let (h, neu, naechster) = integration.schritt(f, 0.5, exakt(0.5), 1.0);
+        assert_eq!(h, integration.min_zeitschritt);
+        assert_eq!(naechster, integration.min_zeitschritt);
+        assert!((neu - exakt(0.5 + h)).abs() < 1e-12);
+    }
+}
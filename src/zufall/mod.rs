@@ -0,0 +1,251 @@
+use serde::{Deserialize, Serialize};
+
+/**
+Reproduzierbarer Zufallszahlengenerator (SplitMix64). Mit demselben Startwert entsteht
+unabhängig von Plattform und Abhängigkeiten immer dieselbe Folge.
+*/
+pub struct Zufall {
+    zustand: u64,
+}
+
+impl Zufall {
+    pub fn neu(startwert: u64) -> Self {
+        Zufall { zustand: startwert }
+    }
+
+    pub fn naechste(&mut self) -> u64 {
+        self.zustand = self.zustand.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.zustand;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    // Gleichverteilt im offenen Intervall (0, 1)
+    pub fn gleichverteilt(&mut self) -> f64 {
+        ((self.naechste() >> 11) as f64 + 0.5) / (1u64 << 53) as f64
+    }
+}
+
+/**
+Verteilung einer unsicheren Eingabe, angegeben mit `verteilung`:
+
+```toml
+{ verteilung = "gleich", min = 0.4, max = 0.6 }
+{ verteilung = "normal", mittelwert = 0.5, standardabweichung = 0.05 }
+{ verteilung = "normal", mittelwert = 0.1, standardabweichung = 0.05, min = 0.0 }
+{ verteilung = "dreieck", min = 0.4, modus = 0.5, max = 0.7 }
+```
+
+Mit `min` und/oder `max` wird die Normalverteilung auf diesen Bereich abgeschnitten,
+etwa für Verlustbeiwerte oder Abmessungen, die nicht negativ werden dürfen.
+*/
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "verteilung", rename_all = "lowercase")]
+pub enum Verteilung {
+    Gleich {
+        min: f64,
+        max: f64,
+    },
+    Normal {
+        mittelwert: f64,
+        standardabweichung: f64,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        min: Option<f64>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max: Option<f64>,
+    },
+    Dreieck {
+        min: f64,
+        modus: f64,
+        max: f64,
+    },
+}
+
+impl Verteilung {
+    // Wert, der mit der Wahrscheinlichkeit `p` nicht überschritten wird
+    pub fn quantil(&self, p: f64) -> f64 {
+        match *self {
+            Verteilung::Gleich { min, max } => min + p * (max - min),
+            Verteilung::Normal {
+                mittelwert,
+                standardabweichung,
+                min,
+                max,
+            } => {
+                // Abgeschnitten: `p` auf den Bereich der Verteilungsfunktion zwischen den Grenzen abbilden
+                let standard = |grenze: f64| (grenze - mittelwert) / standardabweichung;
+                let unten = min.map_or(0.0, |min| standardnormal_verteilung(standard(min)));
+                let oben = max.map_or(1.0, |max| standardnormal_verteilung(standard(max)));
+                let wert = mittelwert
+                    + standardabweichung * standardnormal_quantil(unten + p * (oben - unten));
+                wert.clamp(
+                    min.unwrap_or(f64::NEG_INFINITY),
+                    max.unwrap_or(f64::INFINITY),
+                )
+            }
+            Verteilung::Dreieck { min, modus, max } => {
+                let grenze = (modus - min) / (max - min);
+                if p < grenze {
+                    min + (p * (max - min) * (modus - min)).sqrt()
+                } else {
+                    max - ((1.0 - p) * (max - min) * (max - modus)).sqrt()
+                }
+            }
+        }
+    }
+
+    pub fn ziehe(&self, zufall: &mut Zufall) -> f64 {
+        self.quantil(zufall.gleichverteilt())
+    }
+
+    // Prüft die Grenzen einer abgeschnittenen Normalverteilung
+    pub fn pruefe(&self) -> Result<(), String> {
+        if let Verteilung::Normal {
+            min: Some(min),
+            max: Some(max),
+            ..
+        } = self
+        {
+            if min >= max {
+                return Err(format!("min = {} must be below max = {}", min, max));
+            }
+        }
+        Ok(())
+    }
+}
+
+/**
+Verteilungsfunktion der Standardnormalverteilung über die komplementäre Fehlerfunktion
+nach Numerical Recipes, relativer Fehler unter 1.2e-7.
+*/
+fn standardnormal_verteilung(x: f64) -> f64 {
+    let z = (x / std::f64::consts::SQRT_2).abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let polynom = [
+        -1.26551223,
+        1.00002368,
+        0.37409196,
+        0.09678418,
+        -0.18628806,
+        0.27886807,
+        -1.13520398,
+        1.48851587,
+        -0.82215223,
+        0.17087277,
+    ]
+    .iter()
+    .rev()
+    .fold(0.0, |summe, a| a + t * summe);
+    let erfc = t * (-z * z + polynom).exp();
+    if x < 0.0 {
+        erfc / 2.0
+    } else {
+        1.0 - erfc / 2.0
+    }
+}
+
+/**
+Quantil der Standardnormalverteilung nach Acklam, relativer Fehler unter 1.2e-9.
+*/
+fn standardnormal_quantil(p: f64) -> f64 {
+    const A: [f64; 6] = [
+        -3.969683028665376e+01,
+        2.209460984245205e+02,
+        -2.759285104469687e+02,
+        1.38357751867269e+02,
+        -3.066479806614716e+01,
+        2.506628277459239e+00,
+    ];
+    const B: [f64; 5] = [
+        -5.447609879822406e+01,
+        1.615858368580409e+02,
+        -1.556989798598866e+02,
+        6.680131188771972e+01,
+        -1.328068155288572e+01,
+    ];
+    const C: [f64; 6] = [
+        -7.784894002430293e-03,
+        -3.223964580411365e-01,
+        -2.400758277161838e+00,
+        -2.549732539343734e+00,
+        4.374664141464968e+00,
+        2.938163982698783e+00,
+    ];
+    const D: [f64; 4] = [
+        7.784695709041462e-03,
+        3.224671290700398e-01,
+        2.445134137142996e+00,
+        3.754408661907416e+00,
+    ];
+    const P_UNTEN: f64 = 0.02425;
+
+    let rand = |q: f64| {
+        (((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5])
+            / ((((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1.0)
+    };
+    if p < P_UNTEN {
+        rand((-2.0 * p.ln()).sqrt())
+    } else if p > 1.0 - P_UNTEN {
+        -rand((-2.0 * (1.0 - p).ln()).sqrt())
+    } else {
+        let q = p - 0.5;
+        let r = q * q;
+        (((((A[0] * r + A[1]) * r + A[2]) * r + A[3]) * r + A[4]) * r + A[5]) * q
+            / (((((B[0] * r + B[1]) * r + B[2]) * r + B[3]) * r + B[4]) * r + 1.0)
+    }
+}
+
+/**
+Perzentil `p` (0 bis 100) einer Stichprobe mit linearer Interpolation zwischen den sortierten Werten.
+*/
+pub fn perzentil(sortiert: &[f64], p: f64) -> f64 {
+    if sortiert.is_empty() {
+        return f64::NAN;
+    }
+    let lage = (p / 100.0).clamp(0.0, 1.0) * (sortiert.len() - 1) as f64;
+    let unten = lage.floor() as usize;
+    let oben = lage.ceil() as usize;
+    sortiert[unten] + (sortiert[oben] - sortiert[unten]) * (lage - unten as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verteilungsfunktion_und_quantil_passen_zusammen() {
+        assert!((standardnormal_verteilung(0.0) - 0.5).abs() < 1e-7);
+        assert!((standardnormal_verteilung(1.959964) - 0.975).abs() < 1e-7);
+        for p in [0.001, 0.1, 0.5, 0.8, 0.999] {
+            assert!((standardnormal_verteilung(standardnormal_quantil(p)) - p).abs() < 1e-7);
+        }
+    }
+
+    fn normal(min: Option<f64>, max: Option<f64>) -> Verteilung {
+        Verteilung::Normal {
+            mittelwert: 0.1,
+            standardabweichung: 0.2,
+            min,
+            max,
+        }
+    }
+
+    #[test]
+    fn abgeschnittene_normalverteilung() {
+        let frei = normal(None, None);
+        assert!((frei.quantil(0.5) - 0.1).abs() < 1e-9);
+        assert!(frei.quantil(0.01) < 0.0);
+        let positiv = normal(Some(0.0), None);
+        let mut zufall = Zufall::neu(7);
+        assert!((0..10000).all(|_| positiv.ziehe(&mut zufall) >= 0.0));
+        // Der Median liegt bei der Hälfte der verbleibenden Wahrscheinlichkeit über Φ(-0.5)
+        let unten = standardnormal_verteilung(-0.5);
+        let median = 0.1 + 0.2 * standardnormal_quantil(unten + 0.5 * (1.0 - unten));
+        assert!((positiv.quantil(0.5) - median).abs() < 1e-9);
+        let bereich = normal(Some(0.0), Some(0.2));
+        assert!((bereich.quantil(0.5) - 0.1).abs() < 1e-6);
+        assert!((0..1000).all(|_| (0.0..=0.2).contains(&bereich.ziehe(&mut zufall))));
+        assert!(normal(Some(0.2), Some(0.2)).pruefe().is_err());
+    }
+}
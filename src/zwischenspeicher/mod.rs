@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, OnceLock};
+
+use log::{info, warn};
+
+use crate::auswertung::Kennwerte;
+use crate::eingabe::Schleusenwerte;
+use crate::nachweis::Nachweise;
+use crate::sicherung::Sicherung;
+use crate::versuchsplan::Versuchsplan;
+
+/**
+Zwischenspeicher für die Kennwerte einer Füllung. Schlüssel ist der Fingerabdruck der
+vollständigen Schleuse ohne die Angaben der Studien, sodass gleiche Varianten verschiedener
+Studien oder Suchschritte nur einmal simuliert werden. Mit Datei bleiben die Kennwerte über
+mehrere Programmläufe erhalten, die Datei wird wie eine `Sicherung` fortgeschrieben.
+*/
+pub struct Zwischenspeicher {
+    eintraege: Mutex<HashMap<u64, Kennwerte>>,
+    datei: Option<Sicherung>,
+    treffer: AtomicUsize,
+    simulationen: AtomicUsize,
+}
+
+// Kennung der Datei, ändert sich, wenn sich die gespeicherten Kennwerte oder die Programmversion ändern
+const KENNUNG: &str = concat!(
+    "zwischenspeicher kennwerte 2 version ",
+    env!("CARGO_PKG_VERSION")
+);
+
+static ZWISCHENSPEICHER: OnceLock<Zwischenspeicher> = OnceLock::new();
+
+/**
+Der gemeinsame Zwischenspeicher aller Studien, ohne `verwende_datei` nur im Speicher.
+*/
+pub fn zwischenspeicher() -> &'static Zwischenspeicher {
+    ZWISCHENSPEICHER.get_or_init(|| Zwischenspeicher::neu(None))
+}
+
+/**
+Legt den gemeinsamen Zwischenspeicher zusätzlich in `datei` ab. Muss vor der ersten Simulation
+einer Studie aufgerufen werden.
+*/
+pub fn verwende_datei(datei: &Path) {
+    if ZWISCHENSPEICHER
+        .set(Zwischenspeicher::neu(Some(datei)))
+        .is_err()
+    {
+        warn!("Cache already in use, not reading {}", datei.display());
+    }
+}
+
+impl Zwischenspeicher {
+    pub fn neu(datei: Option<&Path>) -> Self {
+        Zwischenspeicher {
+            eintraege: Mutex::new(HashMap::new()),
+            datei: datei.map(|d| Sicherung::oeffne(d, KENNUNG)),
+            treffer: AtomicUsize::new(0),
+            simulationen: AtomicUsize::new(0),
+        }
+    }
+
+    /**
+    Kennwerte der Füllung der Schleuse, aus dem Zwischenspeicher oder neu simuliert.
+    */
+    pub fn kennwerte(&self, werte: &Schleusenwerte) -> Kennwerte {
+        let schluessel = schluessel(werte);
+        let gespeichert = self
+            .eintraege_sperren()
+            .get(&schluessel)
+            .copied()
+            .or_else(|| {
+                self.datei
+                    .as_ref()
+                    .and_then(|d| d.ergebnis(schluessel as usize))
+                    .and_then(|w| <[f64; 8]>::try_from(w).ok())
+                    .map(aus_feld)
+            });
+        if let Some(k) = gespeichert {
+            self.treffer.fetch_add(1, Ordering::Relaxed);
+            self.eintraege_sperren().insert(schluessel, k);
+            return k;
+        }
+
+        let shl = werte.erschaffe_schleuse();
+        let res = shl.fuell_schleuse();
+        let k = Kennwerte::berechne(&shl, &res);
+        self.simulationen.fetch_add(1, Ordering::Relaxed);
+        self.eintraege_sperren().insert(schluessel, k);
+        if let Some(datei) = &self.datei {
+            datei.speichere(schluessel as usize, &als_feld(&k));
+        }
+        k
+    }
+
+    // Anzahl der Abfragen aus dem Zwischenspeicher und der neuen Simulationen
+    pub fn statistik(&self) -> (usize, usize) {
+        (
+            self.treffer.load(Ordering::Relaxed),
+            self.simulationen.load(Ordering::Relaxed),
+        )
+    }
+
+    pub fn protokolliere(&self) {
+        let (treffer, simulationen) = self.statistik();
+        if treffer + simulationen > 0 {
+            info!("Cache: {} hits, {} simulations", treffer, simulationen);
+        }
+    }
+
+    fn eintraege_sperren(&self) -> std::sync::MutexGuard<'_, HashMap<u64, Kennwerte>> {
+        self.eintraege.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+// Fingerabdruck ohne Nachweise, Unsicherheiten, Versuchsplan und Variationen
+fn schluessel(werte: &Schleusenwerte) -> u64 {
+    let mut schleuse = werte.clone();
+    schleuse.nachweise = Nachweise::default();
+    schleuse.unsicherheiten.clear();
+    schleuse.versuchsplan = Versuchsplan::default();
+    schleuse.variation.clear();
+    schleuse.fingerabdruck()
+}
+
+fn als_feld(k: &Kennwerte) -> [f64; 8] {
+    [
+        k.fuellzeit,
+        k.max_durchfluss,
+        k.max_durchflusszunahme,
+        k.wasserspiegelneigung,
+        k.fuelloeffnungen as f64,
+        k.min_durchfluss,
+        k.min_durchflusszunahme,
+        k.fuellquerschnitte as f64,
+    ]
+}
+
+fn aus_feld(w: [f64; 8]) -> Kennwerte {
+    Kennwerte {
+        fuellzeit: w[0],
+        max_durchfluss: w[1],
+        max_durchflusszunahme: w[2],
+        wasserspiegelneigung: w[3],
+        fuelloeffnungen: w[4] as usize,
+        min_durchfluss: w[5],
+        min_durchflusszunahme: w[6],
+        fuellquerschnitte: w[7] as usize,
+    }
+}